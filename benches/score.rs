@@ -0,0 +1,42 @@
+// Benchmarks comparing the itemized scorer with the packed one.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use cribbagepractice::deck::Deck;
+use cribbagepractice::hand::Hand;
+use cribbagepractice::packed::{self, CardSet};
+
+/// A fixed, spread-out selection of shows, so both scorers see the same work every run.
+fn shows() -> Vec<[cribbagepractice::card::Card; 5]> {
+    let deck = Deck::new();
+    let cards = deck.cards();
+    (0 .. 1000)
+        .map(|i| {
+            let mut show = [cards[0]; 5];
+            for (j, card) in show.iter_mut().enumerate() {
+                *card = cards[(i * 7 + j * 11) % 52];
+            }
+            show
+        })
+        .collect()
+}
+
+fn bench_score(c: &mut Criterion) {
+    let shows = shows();
+
+    c.bench_function("find_all_combos", |b| b.iter(|| {
+        shows.iter().map(|show| Hand::with_starter(black_box(show)).score()).sum::<i32>()
+    }));
+
+    c.bench_function("packed", |b| b.iter(|| {
+        shows.iter()
+            .map(|show| i32::from(packed::score(CardSet::from_cards(black_box(&show[.. 4])), &show[4])))
+            .sum::<i32>()
+    }));
+}
+
+criterion_group!(benches, bench_score);
+criterion_main!(benches);
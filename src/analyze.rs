@@ -0,0 +1,172 @@
+// Analyze :: bulk analysis of hand scores.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::Hand;
+use crate::packed::{self, CardSet};
+
+/// The highest score possible for four cards plus a starter.
+pub const MAX_SCORE: usize = 29;
+
+/// How many hands score each number of points.
+pub struct Distribution {
+    pub counts: [u64; MAX_SCORE + 1],
+}
+
+impl Distribution {
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let points: u64 = self.counts.iter()
+            .enumerate()
+            .map(|(score, count)| score as u64 * count)
+            .sum();
+        points as f64 / self.total() as f64
+    }
+
+    pub fn median(&self) -> usize {
+        let half = self.total().div_ceil(2);
+        let mut seen = 0;
+        for (score, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= half {
+                return score;
+            }
+        }
+        MAX_SCORE
+    }
+
+    /// The scores no hand can make.
+    pub fn impossible(&self) -> Vec<usize> {
+        (0 ..= MAX_SCORE).filter(|&score| self.counts[score] == 0).collect()
+    }
+}
+
+/// Scores every four-card hand with every possible starter, spread across all available cores.
+///
+/// If `distinct` is set, only one show out of each group that differ just by which suit is which
+/// gets scored, and it counts for the whole group.
+pub fn score_distribution(distinct: bool) -> Distribution {
+    let deck = Deck::new();
+    let cards = deck.cards();
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    // Work is handed out by the index of the first card in the hand, so that each thread can
+    // grab another chunk as soon as it's done with one.
+    let next = AtomicUsize::new(0);
+    let total = Mutex::new(Distribution { counts: [0; MAX_SCORE + 1] });
+    thread::scope(|scope| {
+        for _ in 0 .. threads {
+            scope.spawn(|| {
+                let mut counts = [0u64; MAX_SCORE + 1];
+                loop {
+                    let first = next.fetch_add(1, Ordering::Relaxed);
+                    if first >= cards.len() {
+                        break;
+                    }
+                    count_hands_from(cards, first, distinct, &mut counts);
+                }
+
+                let mut total = total.lock().unwrap();
+                for (score, count) in counts.iter().enumerate() {
+                    total.counts[score] += count;
+                }
+            });
+        }
+    });
+    total.into_inner().unwrap()
+}
+
+/// Scores every hand whose lowest-indexed card is `cards[first]`, with every starter.
+fn count_hands_from(cards: &[Card], first: usize, distinct: bool, counts: &mut [u64; MAX_SCORE + 1]) {
+    for_each_show_from(cards, first, |show| {
+        if distinct {
+            let held = CardSet::from_cards(&show[.. 4]);
+            if packed::canonical(held, &show[4]) != (held, show[4]) {
+                return;
+            }
+            let score = Hand::with_starter(show).score();
+            counts[score as usize] += u64::from(packed::multiplicity(held, &show[4]));
+        } else {
+            let score = Hand::with_starter(show).score();
+            counts[score as usize] += 1;
+        }
+    });
+}
+
+/// Calls `f` with every four held cards whose lowest-indexed card is `cards[first]`, followed by
+/// every starter.
+fn for_each_show_from<F: FnMut(&[Card; 5])>(cards: &[Card], first: usize, mut f: F) {
+    let n = cards.len();
+    let mut show = [cards[first]; 5];
+    for second in first + 1 .. n {
+        show[1] = cards[second];
+        for third in second + 1 .. n {
+            show[2] = cards[third];
+            for fourth in third + 1 .. n {
+                show[3] = cards[fourth];
+                for (starter, card) in cards.iter().enumerate() {
+                    if starter == first || starter == second || starter == third || starter == fourth {
+                        continue;
+                    }
+                    show[4] = *card;
+                    f(&show);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_count_hands_from() {
+    // Just the last five cards of the deck: the queen of diamonds and the four kings. Starting
+    // from the queen, the hand holds three of the kings and the fourth is cut.
+    let deck = Deck::new();
+    let cards = &deck.cards()[47 ..];
+    let mut counts = [0; MAX_SCORE + 1];
+    count_hands_from(cards, 0, false, &mut counts);
+    assert_eq!(counts.iter().sum::<u64>(), 4);
+    assert_eq!(counts[12], 4);
+}
+
+#[test]
+fn test_count_distinct_hands() {
+    // Aces through fours: any suit can be swapped for any other without leaving these cards, so
+    // counting only the distinct shows has to come out the same as counting all of them.
+    let deck = Deck::new();
+    let cards = &deck.cards()[.. 16];
+    let mut all = [0; MAX_SCORE + 1];
+    let mut distinct = [0; MAX_SCORE + 1];
+    for first in 0 .. cards.len() {
+        count_hands_from(cards, first, false, &mut all);
+        count_hands_from(cards, first, true, &mut distinct);
+    }
+    assert_eq!(all.iter().sum::<u64>(), 1820 * 12);
+    assert_eq!(distinct, all);
+}
+
+#[test]
+fn test_score_distribution() {
+    // The well-known counts for every show, from scoring one of each group of suit-swapped shows
+    // by finding all the combos, since the score table isn't enabled here.
+    assert!(crate::table::get().is_none());
+    let distribution = score_distribution(true);
+    assert_eq!(distribution.counts, [
+        1009008, 99792, 2813796, 505008, 2855676, 697508, 1800268, 751324, 1137236, 361224,
+        388740, 51680, 317340, 19656, 90100, 9168, 58248, 11196, 2708, 0,
+        8068, 2496, 444, 356, 3680, 0, 0, 0, 76, 4,
+    ]);
+    assert_eq!(distribution.total(), 12_994_800);
+    assert_eq!(distribution.impossible(), vec![19, 25, 26, 27]);
+    assert_eq!(distribution.median(), 4);
+    assert!((distribution.mean() - 4.769).abs() < 0.001);
+}
@@ -7,7 +7,7 @@ use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum Suit {
     Spades,
     Clubs,
@@ -15,7 +15,96 @@ pub enum Suit {
     Diamonds,
 }
 
-#[derive(Debug, PartialEq)]
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spades, Suit::Clubs, Suit::Hearts, Suit::Diamonds];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Suit::Spades   => "spades",
+            Suit::Clubs    => "clubs",
+            Suit::Hearts   => "hearts",
+            Suit::Diamonds => "diamonds",
+        }
+    }
+}
+
+const NUMBER_NAMES: [(&str, &str); 13] = [
+    ("ace", "aces"), ("two", "twos"), ("three", "threes"), ("four", "fours"), ("five", "fives"),
+    ("six", "sixes"), ("seven", "sevens"), ("eight", "eights"), ("nine", "nines"), ("ten", "tens"),
+    ("jack", "jacks"), ("queen", "queens"), ("king", "kings"),
+];
+
+/// The name of a card number, like "five" or "jack".
+pub fn number_name(number: i8) -> &'static str {
+    NUMBER_NAMES[number as usize - 1].0
+}
+
+/// The plural name of a card number, like "fives" or "jacks".
+pub fn number_plural(number: i8) -> &'static str {
+    NUMBER_NAMES[number as usize - 1].1
+}
+
+/// The number as it's written on the card: A, 2 through 10, J, Q, or K.
+pub fn number_symbol(number: i8) -> String {
+    match number {
+        1  => "A".to_owned(),
+        11 => "J".to_owned(),
+        12 => "Q".to_owned(),
+        13 => "K".to_owned(),
+        _  => format!("{}", number),
+    }
+}
+
+/// Parses a card number on its own, without a suit: 1-13, or A, J, Q, K.
+pub fn number_from_symbol(s: &str) -> Option<i8> {
+    let number = match s {
+        "a" | "A" => 1,
+        "j" | "J" => 11,
+        "q" | "Q" => 12,
+        "k" | "K" => 13,
+        _ => s.parse().ok()?,
+    };
+    if (1 ..= 13).contains(&number) {
+        Some(number)
+    } else {
+        None
+    }
+}
+
+/// Picks a new suit for each suit, so that the suits are put in order of the given signature,
+/// highest first. Suits with the same signature are interchangeable, so it doesn't matter which of
+/// them comes first. The result is indexed by the old suit.
+pub fn canonical_suits<F: Fn(Suit) -> u32>(signature: F) -> [Suit; 4] {
+    let mut order = Suit::ALL;
+    order.sort_by_key(|&suit| std::cmp::Reverse(signature(suit)));
+
+    let mut to = Suit::ALL;
+    for (new, old) in order.iter().enumerate() {
+        to[*old as usize] = Suit::ALL[new];
+    }
+    to
+}
+
+/// How many ways the suits can be relabeled that give different results, when suits with the same
+/// signature are interchangeable: 4! divided by the ways of shuffling each group of ties.
+pub fn suit_multiplicity<F: Fn(Suit) -> u32>(signature: F) -> u32 {
+    let mut signatures: Vec<u32> = Suit::ALL.iter().map(|&suit| signature(suit)).collect();
+    signatures.sort();
+
+    let mut multiplicity = 24;
+    let mut run = 1;
+    for i in 1 ..= signatures.len() {
+        if i < signatures.len() && signatures[i] == signatures[i - 1] {
+            run += 1;
+        } else {
+            multiplicity /= (1 ..= run).product::<u32>();
+            run = 1;
+        }
+    }
+    multiplicity
+}
+
+#[derive(Debug, PartialEq, Hash, Clone, Copy)]
 pub struct Card {
     pub suit: Suit,
     pub number: i8,
@@ -30,19 +119,24 @@ impl Card {
             self.number
         }
     }
+
+    /// Where the card is in a new deck, from 0 to 51: ordered by number, then by suit.
+    pub fn index(&self) -> usize {
+        (self.number as usize - 1) * 4 + self.suit as usize
+    }
+
+    pub fn from_index(index: usize) -> Card {
+        Card {
+            suit: Suit::ALL[index % 4],
+            number: (index / 4) as i8 + 1,
+        }
+    }
 }
 
 impl Display for Card {
+    #[allow(clippy::single_char_add_str)]
     fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
-        let mut out = String::new();
-
-        match self.number {
-            1  => out.push_str("A"),
-            11 => out.push_str("J"),
-            12 => out.push_str("Q"),
-            13 => out.push_str("K"),
-            _  => out.push_str(&format!("{}", self.number)),
-        }
+        let mut out = number_symbol(self.number);
 
         match self.suit {
             Suit::Spades    => out.push_str("S"),
@@ -58,6 +152,16 @@ impl Display for Card {
 impl Eq for Card {
 }
 
+#[test]
+fn test_suit_multiplicity() {
+    assert_eq!(suit_multiplicity(|_| 0), 1);
+    assert_eq!(suit_multiplicity(|suit| suit as u32), 24);
+    assert_eq!(suit_multiplicity(|suit| if suit == Suit::Spades { 1 } else { 0 }), 4);
+    assert_eq!(suit_multiplicity(|suit| (suit as u32) / 2), 6);
+    assert_eq!(canonical_suits(|suit| suit as u32),
+        [Suit::Diamonds, Suit::Hearts, Suit::Clubs, Suit::Spades]);
+}
+
 #[derive(Debug)]
 pub struct CardParseError {
     message: String,
@@ -83,6 +187,7 @@ impl FromStr for Card {
     type Err = CardParseError;
 
     /// Parses strings of the form "<number><suit>" where "<number>" is 1-13 or A, J, Q, K; and "<suit>" is S, C, D, or H.
+    #[allow(clippy::needless_late_init, clippy::manual_range_contains)]
     fn from_str(s: &str) -> Result<Card, CardParseError> {
         let (suit_byte_index, suit_char) =
             s.char_indices()
@@ -123,6 +228,12 @@ impl FromStr for Card {
     }
 }
 
+/// Parses cards separated by spaces, for tests.
+#[cfg(test)]
+pub fn parse_cards(s: &str) -> Vec<Card> {
+    s.split(' ').map(|x| Card::from_str(x).unwrap()).collect()
+}
+
 #[test]
 fn test_card_parse() {
     assert_eq!(Card::from_str("ks").unwrap(), Card { number: 13, suit: Suit::Spades });
@@ -132,5 +243,8 @@ fn test_card_parse() {
     assert!(Card::from_str("14s").is_err());
     assert!(Card::from_str("0s").is_err());
     assert!(Card::from_str("barf").is_err());
+    assert_eq!(number_from_symbol("q"), Some(12));
+    assert_eq!(number_from_symbol("10"), Some(10));
+    assert_eq!(number_from_symbol("14"), None);
     assert!(Card::from_str("").is_err());
 }
@@ -0,0 +1,181 @@
+// Catch :: the computer counts its own hand, sometimes wrongly, and the player has to catch it.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::card::Card;
+use crate::combo::{self, Combo, ComboKind};
+use crate::hand::Hand;
+
+/// The combos the computer announces for its hand. With the given probability, it makes one
+/// mistake: leaving a combo out, counting a pair twice, or counting a combo that isn't there.
+pub fn announce<'h, R: Rng>(hand: &'h Hand<'_>, error_rate: f64, rng: &mut R) -> Vec<Combo<'h>> {
+    let mut combos = hand.find_all_combos();
+    if !rng.gen_bool(error_rate) {
+        return combos;
+    }
+
+    let pairs: Vec<usize> = (0 .. combos.len())
+        .filter(|&i| combos[i].kind == ComboKind::Pair)
+        .collect();
+    match rng.gen_range(0, 3) {
+        0 if !combos.is_empty() => {
+            let index = rng.gen_range(0, combos.len());
+            combos.remove(index);
+        },
+        1 if !pairs.is_empty() => {
+            let pair = combos[*pairs.choose(rng).unwrap()].clone();
+            combos.push(pair);
+        },
+        _ => {
+            let misses = combo::near_misses(hand.cards);
+            let phantom = match misses.choose(rng) {
+                Some(phantom) => phantom.clone(),
+                // Nothing even comes close; any two cards that don't make fifteen will do.
+                None => {
+                    let cards = hand.cards;
+                    let (first, second) = (0 .. cards.len())
+                        .flat_map(|i| (i + 1 .. cards.len()).map(move |j| (i, j)))
+                        .find(|&(i, j)| cards[i].value() + cards[j].value() != 15)
+                        .unwrap();
+                    Combo::new(vec![&cards[first], &cards[second]], ComboKind::Fifteen)
+                },
+            };
+            combos.push(phantom);
+        },
+    }
+    combos
+}
+
+/// Where an announced count differs from the real one.
+pub struct Check<'a> {
+    /// Combos that are there but weren't announced.
+    pub missed: Vec<Combo<'a>>,
+    /// Combos that were announced but aren't there, or were announced more than once.
+    pub bogus: Vec<Combo<'a>>,
+}
+
+pub fn check<'a>(actual: &[Combo<'a>], announced: &[Combo<'a>]) -> Check<'a> {
+    let mut missed: Vec<Combo<'a>> = actual.to_vec();
+    let mut bogus = vec![];
+    for combo in announced {
+        match missed.iter().position(|x| same_combo(x, combo)) {
+            Some(index) => {
+                missed.remove(index);
+            },
+            None => bogus.push(combo.clone()),
+        }
+    }
+    Check { missed, bogus }
+}
+
+/// Whether a combo announced wrongly is really in the hand, and was only counted once too often.
+pub fn counted_twice(hand: &Hand<'_>, combo: &Combo<'_>) -> bool {
+    hand.find_all_combos().iter().any(|x| same_combo(x, combo))
+}
+
+fn same_combo(a: &Combo<'_>, b: &Combo<'_>) -> bool {
+    a.kind == b.kind && a == &b.cards[..]
+}
+
+/// How the player did at catching the computer's mistakes.
+pub struct Verdict<'a> {
+    /// Combos the computer left out, which the player pointed out.
+    pub caught_missed: Vec<Combo<'a>>,
+    /// Combos the computer shouldn't have counted, which the player pointed out.
+    pub caught_bogus: Vec<Combo<'a>>,
+    /// Mistakes the player didn't point out.
+    pub uncaught_missed: Vec<Combo<'a>>,
+    pub uncaught_bogus: Vec<Combo<'a>>,
+    /// Cards the player called out that weren't a mistake.
+    pub wrong_calls: Vec<Vec<Card>>,
+}
+
+/// Matches the player's calls, each a set of cards, against the computer's mistakes.
+pub fn judge<'a>(check: Check<'a>, calls: &[Vec<Card>]) -> Verdict<'a> {
+    let Check { mut missed, mut bogus } = check;
+    let mut verdict = Verdict {
+        caught_missed: vec![],
+        caught_bogus: vec![],
+        uncaught_missed: vec![],
+        uncaught_bogus: vec![],
+        wrong_calls: vec![],
+    };
+    for call in calls {
+        if let Some(index) = missed.iter().position(|x| x == &call[..]) {
+            verdict.caught_missed.push(missed.remove(index));
+        } else if let Some(index) = bogus.iter().position(|x| x == &call[..]) {
+            verdict.caught_bogus.push(bogus.remove(index));
+        } else {
+            verdict.wrong_calls.push(call.clone());
+        }
+    }
+    verdict.uncaught_missed = missed;
+    verdict.uncaught_bogus = bogus;
+    verdict
+}
+
+#[test]
+fn test_announce() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let actual = hand.find_all_combos();
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let honest = announce(&hand, 0., &mut rng);
+    let check_honest = check(&actual, &honest);
+    assert!(check_honest.missed.is_empty() && check_honest.bogus.is_empty());
+
+    for _ in 0 .. 20 {
+        let announced = announce(&hand, 1., &mut rng);
+        let check = check(&actual, &announced);
+        assert_eq!(check.missed.len() + check.bogus.len(), 1);
+        for combo in &check.bogus {
+            // A pair counted twice is a real combo; anything else mustn't be.
+            assert!(combo.kind == ComboKind::Pair || !counted_twice(&hand, combo));
+        }
+    }
+
+    // With nothing close to a combo, the made-up fifteen mustn't be one that's there: here, the
+    // first two cards make fifteen.
+    let cards = parse_cards("5s 10s 2s 5c 5h");
+    let hand = Hand::with_starter(&cards);
+    assert!(combo::near_misses(&cards).is_empty());
+    for _ in 0 .. 20 {
+        for combo in check(&hand.find_all_combos(), &announce(&hand, 1., &mut rng)).bogus {
+            assert!(combo.kind == ComboKind::Pair || !counted_twice(&hand, &combo));
+        }
+    }
+}
+
+#[test]
+fn test_judge() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let actual = hand.find_all_combos();
+
+    // Leave out the pair, and count a fifteen that's really 16.
+    let mut announced: Vec<Combo<'_>> = actual.iter()
+        .filter(|combo| combo.kind != ComboKind::Pair)
+        .cloned()
+        .collect();
+    announced.push(Combo::new(vec![&cards[0], &cards[1], &cards[4]], ComboKind::Fifteen));
+
+    let verdict = judge(check(&actual, &announced), &[parse_cards("5d 5h"), parse_cards("3s 4s")]);
+    assert_eq!(verdict.caught_missed.len(), 1);
+    assert_eq!(verdict.caught_missed[0].kind, ComboKind::Pair);
+    assert!(verdict.caught_bogus.is_empty());
+    assert!(verdict.uncaught_missed.is_empty());
+    assert_eq!(verdict.uncaught_bogus.len(), 1);
+    assert_eq!(verdict.wrong_calls, vec![parse_cards("3s 4s")]);
+}
+
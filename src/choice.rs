@@ -0,0 +1,142 @@
+// Choice :: multiple-choice questions about a hand, for beginners.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// The wrong answers are made from the mistakes people actually make: leaving a combo out, counting
+// a pair twice, or counting cards that come to 14 or 16 as a fifteen.
+//
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::combo::{self, Combo, ComboKind};
+use crate::hand::Hand;
+
+pub const CHOICES: usize = 4;
+
+pub enum Question<'a> {
+    /// Which of these is the hand's total?
+    Total { choices: Vec<i32>, answer: usize },
+    /// Which of these combos doesn't score?
+    Decoy { choices: Vec<Combo<'a>>, answer: usize },
+}
+
+impl<'a> Question<'a> {
+    pub fn answer(&self) -> usize {
+        match self {
+            Question::Total { answer, .. } | Question::Decoy { answer, .. } => *answer,
+        }
+    }
+}
+
+/// Asks one or the other kind of question, as long as the hand has enough combos for a decoy
+/// question.
+pub fn ask<'h, R: Rng>(hand: &'h Hand<'_>, rng: &mut R) -> Question<'h> {
+    if rng.gen_bool(0.5) {
+        if let Some(question) = decoy_question(hand, rng) {
+            return question;
+        }
+    }
+    total_question(hand, rng)
+}
+
+pub fn total_question<R: Rng>(hand: &Hand<'_>, rng: &mut R) -> Question<'static> {
+    let combos = hand.find_all_combos();
+    let total = combos.iter().fold(0, |total, combo| total + i32::from(combo.score));
+
+    let mut near = vec![];
+    for combo in &combos {
+        near.push(total - i32::from(combo.score));
+        if combo.kind == ComboKind::Pair {
+            near.push(total + i32::from(combo.score));
+        }
+    }
+    for miss in combo::near_misses(hand.cards) {
+        near.push(total + i32::from(miss.score));
+    }
+    near.retain(|&score| score >= 0 && score != total);
+    near.sort();
+    near.dedup();
+    near.shuffle(rng);
+
+    let mut choices = vec![total];
+    choices.extend(near.into_iter().take(CHOICES - 1));
+    // Not enough near misses; pad with totals close by.
+    let mut offset = 1;
+    while choices.len() < CHOICES {
+        for score in [total + offset, total - offset].iter() {
+            if *score >= 0 && !choices.contains(score) && choices.len() < CHOICES {
+                choices.push(*score);
+            }
+        }
+        offset += 1;
+    }
+
+    choices.shuffle(rng);
+    let answer = choices.iter().position(|&score| score == total).unwrap();
+    Question::Total { choices, answer }
+}
+
+/// Three real combos and one near miss, or `None` if the hand doesn't have that many of each.
+pub fn decoy_question<'h, R: Rng>(hand: &'h Hand<'_>, rng: &mut R) -> Option<Question<'h>> {
+    let combos = hand.find_all_combos();
+    let misses = combo::near_misses(hand.cards);
+    if combos.len() < CHOICES - 1 {
+        return None;
+    }
+    let decoy = misses.choose(rng)?.clone();
+
+    let mut choices: Vec<Combo<'h>> = combos.choose_multiple(rng, CHOICES - 1).cloned().collect();
+    let answer = rng.gen_range(0, CHOICES);
+    choices.insert(answer, decoy);
+    Some(Question::Decoy { choices, answer })
+}
+
+#[test]
+fn test_questions() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::card::parse_cards;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let total = hand.score();
+
+    for _ in 0 .. 20 {
+        match total_question(&hand, &mut rng) {
+            Question::Total { choices, answer } => {
+                assert_eq!(choices.len(), CHOICES);
+                assert_eq!(choices[answer], total);
+                assert_eq!(choices.iter().filter(|&&score| score == total).count(), 1);
+            },
+            _ => panic!("expected a total question"),
+        }
+
+        let question = decoy_question(&hand, &mut rng).unwrap();
+        let answer = question.answer();
+        match question {
+            Question::Decoy { choices, .. } => {
+                let combos = hand.find_all_combos();
+                for (i, choice) in choices.iter().enumerate() {
+                    let real = combos.iter().any(|x| x.kind == choice.kind && x == &choice.cards[..]);
+                    assert_eq!(real, i != answer);
+                }
+            },
+            _ => panic!("expected a decoy question"),
+        }
+    }
+
+    // A hand with nothing in it still gets four different totals to choose from.
+    let cards = parse_cards("kh qc 9s 7d 2h");
+    let hand = Hand::with_starter(&cards);
+    assert!(decoy_question(&hand, &mut rng).is_none());
+    match total_question(&hand, &mut rng) {
+        Question::Total { mut choices, .. } => {
+            choices.sort();
+            choices.dedup();
+            assert_eq!(choices.len(), CHOICES);
+        },
+        _ => panic!("expected a total question"),
+    }
+}
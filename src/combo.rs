@@ -3,13 +3,271 @@
 // Copyright (c) 2016 by William R. Fraser
 //
 
+use std::fmt::{self, Display, Formatter};
+
 use crate::card::Card;
+use crate::util;
+
+/// The broad kinds of combos a player announces when counting a hand.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Category {
+    Fifteens,
+    Pairs,
+    Runs,
+    Flush,
+    Nobs,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Fifteens,
+        Category::Pairs,
+        Category::Runs,
+        Category::Flush,
+        Category::Nobs,
+    ];
+
+    /// Looks up a category by the name a player would use for it, singular or plural.
+    pub fn from_name(name: &str) -> Option<Category> {
+        match name.to_lowercase().as_str() {
+            "fifteen" | "fifteens" => Some(Category::Fifteens),
+            "pair" | "pairs" => Some(Category::Pairs),
+            "run" | "runs" => Some(Category::Runs),
+            "flush" | "flushes" => Some(Category::Flush),
+            "nob" | "nobs" => Some(Category::Nobs),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match *self {
+            Category::Fifteens => "fifteens",
+            Category::Pairs    => "pairs",
+            Category::Runs     => "runs",
+            Category::Flush    => "flush",
+            Category::Nobs     => "nobs",
+        })
+    }
+}
 
-#[derive(Debug)]
+/// Exactly what kind of scoring combination a `Combo` is.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ComboKind {
+    Fifteen,
+    /// Two of a kind.
+    Pair,
+    /// Three of a kind.
+    PairRoyal,
+    /// Four of a kind.
+    DoublePairRoyal,
+    /// A run of the given length.
+    Run(usize),
+    /// A flush of the given number of cards.
+    Flush(usize),
+    /// The jack of the same suit as the starter.
+    Nobs,
+    /// A jack turned up as the starter, which the dealer pegs when it's cut rather than in the count.
+    Heels,
+    /// Two runs of the given length sharing all but one card, along with their pair.
+    DoubleRun(usize),
+    /// Three runs of the given length over a pair royal.
+    TripleRun(usize),
+    /// Four runs of the given length over two pairs.
+    DoubleDoubleRun(usize),
+}
+
+impl ComboKind {
+    /// The kind of an n-of-a-kind group with the given number of cards.
+    pub fn of_a_kind(n: usize) -> ComboKind {
+        match n {
+            2 => ComboKind::Pair,
+            3 => ComboKind::PairRoyal,
+            4 => ComboKind::DoublePairRoyal,
+            _ => panic!("no such thing as {} of a kind", n),
+        }
+    }
+
+    /// How many cards of the same number make up an n-of-a-kind, or zero for other kinds.
+    pub fn of_a_kind_count(self) -> usize {
+        match self {
+            ComboKind::Pair => 2,
+            ComboKind::PairRoyal => 3,
+            ComboKind::DoublePairRoyal => 4,
+            _ => 0,
+        }
+    }
+
+    pub fn score(self) -> i8 {
+        match self {
+            ComboKind::Fifteen => 2,
+            ComboKind::Pair | ComboKind::PairRoyal | ComboKind::DoublePairRoyal => {
+                // Every two cards of the group make a pair worth two.
+                2 * util::binomial_coefficient(self.of_a_kind_count() as i8, 2)
+            },
+            ComboKind::Run(n) | ComboKind::Flush(n) => n as i8,
+            ComboKind::Nobs => 1,
+            ComboKind::Heels => 2,
+            ComboKind::DoubleRun(n) => 2 * n as i8 + 2,
+            ComboKind::TripleRun(n) => 3 * n as i8 + 6,
+            ComboKind::DoubleDoubleRun(n) => 4 * n as i8 + 4,
+        }
+    }
+
+    pub fn category(self) -> Category {
+        match self {
+            ComboKind::Fifteen => Category::Fifteens,
+            ComboKind::Pair | ComboKind::PairRoyal | ComboKind::DoublePairRoyal => Category::Pairs,
+            ComboKind::Run(_)
+                | ComboKind::DoubleRun(_)
+                | ComboKind::TripleRun(_)
+                | ComboKind::DoubleDoubleRun(_) => Category::Runs,
+            ComboKind::Flush(_) => Category::Flush,
+            ComboKind::Nobs | ComboKind::Heels => Category::Nobs,
+        }
+    }
+}
+
+impl Display for ComboKind {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            ComboKind::Fifteen         => fmt.write_str("fifteen"),
+            ComboKind::Pair            => fmt.write_str("pair"),
+            ComboKind::PairRoyal       => fmt.write_str("pair royal"),
+            ComboKind::DoublePairRoyal => fmt.write_str("double pair royal"),
+            ComboKind::Run(n)          => write!(fmt, "run of {}", n),
+            ComboKind::Flush(n)        => write!(fmt, "{}-flush", n),
+            ComboKind::Nobs            => fmt.write_str("nobs"),
+            ComboKind::Heels           => fmt.write_str("his heels"),
+            ComboKind::DoubleRun(n)       => write!(fmt, "double run of {}", n),
+            ComboKind::TripleRun(n)       => write!(fmt, "triple run of {}", n),
+            ComboKind::DoubleDoubleRun(n) => write!(fmt, "double double run of {}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Combo<'a> {
     pub cards: Vec<&'a Card>,
     pub score: i8,
-    pub text: String,
+    pub kind: ComboKind,
+}
+
+impl<'a> Combo<'a> {
+    pub fn new(cards: Vec<&'a Card>, kind: ComboKind) -> Combo<'a> {
+        Combo {
+            cards,
+            score: kind.score(),
+            kind,
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        self.kind.category()
+    }
+
+    /// Breaks an n-of-a-kind into the individual pairs it's made of. Any other combo comes back
+    /// unchanged.
+    pub fn pairs(&self) -> Vec<Combo<'a>> {
+        if self.kind.of_a_kind_count() < 3 {
+            return vec![self.clone()];
+        }
+
+        let mut pairs = vec![];
+        for (i, first) in self.cards.iter().enumerate() {
+            for second in &self.cards[i + 1 ..] {
+                pairs.push(Combo::new(vec![*first, *second], ComboKind::Pair));
+            }
+        }
+        pairs
+    }
+}
+
+/// Replaces each n-of-a-kind with its individual pairs, for players who count them pair by pair.
+pub fn split_pairs<'a>(combos: Vec<Combo<'a>>) -> Vec<Combo<'a>> {
+    combos.iter().flat_map(Combo::pairs).collect()
+}
+
+/// A compound combo, such as a double run, along with the indices of the itemized combos it's made
+/// up of.
+pub struct Compound<'a> {
+    pub combo: Combo<'a>,
+    pub components: Vec<usize>,
+}
+
+/// Finds the runs that share the same numbers, and names them together with their pairs as a
+/// double run, triple run, or double double run.
+pub fn find_compounds<'a>(combos: &[Combo<'a>]) -> Vec<Compound<'a>> {
+    let runs: Vec<usize> = (0 .. combos.len())
+        .filter(|&i| matches!(combos[i].kind, ComboKind::Run(_)))
+        .collect();
+    let by_numbers = util::group_by(&runs, |&i| {
+        let mut numbers: Vec<i8> = combos[i].cards.iter().map(|card| card.number).collect();
+        numbers.sort();
+        numbers
+    });
+
+    let mut compounds = vec![];
+    for (numbers, group) in &by_numbers {
+        if group.len() < 2 {
+            continue;
+        }
+
+        let mut cards: Vec<&'a Card> = vec![];
+        for &&i in group {
+            for card in &combos[i].cards {
+                if !cards.contains(card) {
+                    cards.push(*card);
+                }
+            }
+        }
+
+        let mut dupes: Vec<usize> = util::group_by(&cards, |card| card.number)
+            .values()
+            .map(Vec::len)
+            .filter(|&n| n > 1)
+            .collect();
+        dupes.sort();
+        let kind = match dupes.as_slice() {
+            [2] => ComboKind::DoubleRun(numbers.len()),
+            [3] => ComboKind::TripleRun(numbers.len()),
+            [2, 2] => ComboKind::DoubleDoubleRun(numbers.len()),
+            _ => continue,
+        };
+
+        let mut components: Vec<usize> = group.iter().map(|&&i| i).collect();
+        components.extend((0 .. combos.len()).filter(|&i| {
+            combos[i].category() == Category::Pairs && numbers.contains(&combos[i].cards[0].number)
+        }));
+        components.sort();
+
+        // If the pairs have already been taken away, it's just runs.
+        let combo = Combo::new(cards, kind);
+        if components.iter().fold(0, |total, &i| total + combos[i].score) != combo.score {
+            continue;
+        }
+
+        compounds.push(Compound {
+            combo,
+            components,
+        });
+    }
+    compounds
+}
+
+/// Replaces the runs and pairs that make up a double run, triple run, or double double run with
+/// the compound combo. Anything not part of one comes back unchanged.
+pub fn combine_compounds<'a>(mut combos: Vec<Combo<'a>>) -> Vec<Combo<'a>> {
+    for compound in find_compounds(&combos).into_iter().rev() {
+        combos = combos.into_iter()
+            .enumerate()
+            .filter(|(i, _)| !compound.components.contains(i))
+            .map(|(_, combo)| combo)
+            .collect();
+        combos.push(compound.combo);
+    }
+    combos
 }
 
 impl<'a> PartialEq<[&'a Card]> for Combo<'a> {
@@ -41,3 +299,142 @@ impl<'a> PartialEq<[Card]> for Combo<'a> {
         true
     }
 }
+
+/// Total points scored by the combos of the given category.
+pub fn category_total(combos: &[Combo<'_>], category: Category) -> i8 {
+    combos.iter()
+        .filter(|combo| combo.category() == category)
+        .fold(0, |total, combo| total + combo.score)
+}
+
+/// The combos of the category that a total came up short on, past the points claimed. Which ones
+/// were missed can't be told from a total, so it's the last few; n-of-a-kinds are broken into pairs.
+pub fn shortfall<'a>(combos: &[Combo<'a>], category: Category, claimed: i8) -> Vec<Combo<'a>> {
+    let mut counted = 0;
+    combos.iter()
+        .filter(|combo| combo.category() == category)
+        .flat_map(Combo::pairs)
+        .filter(|combo| {
+            counted += combo.score;
+            counted > claimed
+        })
+        .collect()
+}
+
+/// Sets of cards that nearly make a combo but don't, labeled as the combo they'd be mistaken for:
+/// fifteens that come to 14 or 16, and runs of three with a gap in them.
+pub fn near_misses(cards: &[Card]) -> Vec<Combo<'_>> {
+    let mut misses = vec![];
+    for set in util::power_set(cards) {
+        if set.len() < 2 {
+            continue;
+        }
+        let sum: i8 = set.iter().map(|card| card.value()).sum();
+        if sum == 14 || sum == 16 {
+            misses.push(Combo::new(set.clone(), ComboKind::Fifteen));
+        }
+        if set.len() == 3 {
+            let mut numbers: Vec<i8> = set.iter().map(|card| card.number).collect();
+            numbers.sort();
+            numbers.dedup();
+            if numbers.len() == 3 && numbers[2] - numbers[0] == 3 {
+                misses.push(Combo::new(set, ComboKind::Run(3)));
+            }
+        }
+    }
+    misses
+}
+
+#[test]
+fn test_category_total() {
+    use std::str::FromStr;
+    use crate::hand::Hand;
+
+    let cards: Vec<Card> = ["5h", "5d", "kh", "3s", "4s"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let hand = Hand::new(&cards);
+    let combos = hand.find_all_combos();
+    assert_eq!(category_total(&combos, Category::Fifteens), 4);
+    assert_eq!(category_total(&combos, Category::Pairs), 2);
+    assert_eq!(category_total(&combos, Category::Runs), 6);
+    assert_eq!(category_total(&combos, Category::Flush), 0);
+    assert_eq!(category_total(&combos, Category::Nobs), 0);
+    assert_eq!(Category::from_name("Fifteens"), Some(Category::Fifteens));
+    assert_eq!(Category::from_name("run"), Some(Category::Runs));
+    assert_eq!(Category::from_name("nob"), Some(Category::Nobs));
+    assert_eq!(Category::from_name("heels"), None);
+}
+
+#[test]
+fn test_near_misses() {
+    use std::str::FromStr;
+
+    let cards: Vec<Card> = ["3h", "4d", "6s", "kc"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let misses: Vec<String> = near_misses(&cards).iter()
+        .map(|combo| format!("{} {}", crate::hand::cards_str(&combo.cards), combo.kind))
+        .collect();
+    assert_eq!(misses, vec!["3H 4D 6S run of 3", "4D KC fifteen", "6S KC fifteen"]);
+}
+
+#[test]
+fn test_split_pairs() {
+    use std::str::FromStr;
+    use crate::hand::{self, Hand};
+
+    let cards: Vec<Card> = ["5h", "5d", "5s", "2c", "3c"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let hand = Hand::new(&cards);
+    let combos: Vec<Combo<'_>> = hand.find_all_combos().into_iter()
+        .filter(|combo| combo.category() == Category::Pairs)
+        .collect();
+    assert_eq!(combos.len(), 1);
+    assert_eq!(combos[0].kind, ComboKind::PairRoyal);
+
+    let pairs = split_pairs(combos);
+    assert_eq!(pairs.len(), 3);
+    assert!(pairs.iter().all(|combo| combo.kind == ComboKind::Pair && combo.score == 2));
+    assert_eq!(hand::cards_str(&pairs[0].cards), "5H 5D");
+    assert_eq!(hand::cards_str(&pairs[1].cards), "5H 5S");
+    assert_eq!(hand::cards_str(&pairs[2].cards), "5D 5S");
+}
+
+#[test]
+fn test_compounds() {
+    use crate::card::parse_cards;
+    use crate::hand::{self, Hand};
+
+    let kinds = |input: &str| -> Vec<(ComboKind, i8)> {
+        let cards = parse_cards(input);
+        let hand = Hand::new(&cards);
+        let combos = hand.find_all_combos();
+        let total = combos.iter().fold(0, |total, combo| total + combo.score);
+        let combined = combine_compounds(combos);
+        assert_eq!(total, combined.iter().fold(0, |total, combo| total + combo.score));
+        combined.into_iter()
+            .filter(|combo| combo.category() != Category::Fifteens)
+            .map(|combo| (combo.kind, combo.score))
+            .collect()
+    };
+
+    assert_eq!(kinds("3s 4s 5h 5d kc"), vec![(ComboKind::DoubleRun(3), 8)]);
+    assert_eq!(kinds("3s 4s 5h 5d 6c"), vec![(ComboKind::DoubleRun(4), 10)]);
+    assert_eq!(kinds("3s 4s 4h 4d kc"), vec![(ComboKind::PairRoyal, 6)]);
+    assert_eq!(kinds("3s 4s 5s 4h 4d"), vec![(ComboKind::TripleRun(3), 15)]);
+    assert_eq!(kinds("3s 4s 5s 4h 5d"), vec![(ComboKind::DoubleDoubleRun(3), 16)]);
+    assert_eq!(kinds("3s 4s 5s 7h 8d"), vec![(ComboKind::Run(3), 3)]);
+
+    let cards = parse_cards("3s 4s 5h 5d kc");
+    let hand = Hand::new(&cards);
+    let combined = combine_compounds(hand.find_all_combos());
+    let double_run = combined.last().unwrap();
+    assert_eq!(hand::cards_str(&double_run.cards), "3S 4S 5H 5D");
+
+    // Not a double run anymore without its pair.
+    let mut combos = hand.find_all_combos();
+    combos.retain(|combo| combo.kind != ComboKind::Pair);
+    assert!(find_compounds(&combos).is_empty());
+}
@@ -0,0 +1,104 @@
+// Cross :: cross-cribbage, where a 5x5 grid of cards is scored row by row and column by column.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// Every row and every column is a five-card hand on its own, with no starter, so a flush has to be
+// all five cards. There's also a crib of four cards, which does have a starter.
+//
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::Hand;
+
+pub const SIZE: usize = 5;
+
+/// One of the hands in the grid.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl Line {
+    /// Every row, and then every column.
+    pub fn all() -> Vec<Line> {
+        (0 .. SIZE).map(Line::Row).chain((0 .. SIZE).map(Line::Column)).collect()
+    }
+}
+
+impl Display for Line {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Row(row) => write!(fmt, "row {}", row + 1),
+            Line::Column(column) => write!(fmt, "column {}", column + 1),
+        }
+    }
+}
+
+pub struct CrossGrid {
+    /// The grid, a row at a time.
+    pub cards: Vec<Card>,
+    /// The crib, followed by the starter.
+    pub crib: Vec<Card>,
+}
+
+impl CrossGrid {
+    /// Lays out the top of the deck: the grid, then the crib, then the starter.
+    pub fn deal(deck: &Deck) -> CrossGrid {
+        let cards = deck.cards();
+        CrossGrid {
+            cards: cards[.. SIZE * SIZE].to_vec(),
+            crib: cards[SIZE * SIZE ..= SIZE * SIZE + 4].to_vec(),
+        }
+    }
+
+    pub fn card(&self, row: usize, column: usize) -> &Card {
+        &self.cards[row * SIZE + column]
+    }
+
+    pub fn line(&self, line: Line) -> Vec<Card> {
+        match line {
+            Line::Row(row) => self.cards[row * SIZE .. (row + 1) * SIZE].to_vec(),
+            Line::Column(column) => (0 .. SIZE).map(|row| *self.card(row, column)).collect(),
+        }
+    }
+
+    pub fn crib(&self) -> Hand<'_> {
+        Hand::crib(&self.crib)
+    }
+
+    /// The total of all the rows, and of all the columns.
+    pub fn totals(&self) -> (i32, i32) {
+        let mut rows = 0;
+        let mut columns = 0;
+        for line in Line::all() {
+            let score = Hand::new(&self.line(line)).score();
+            match line {
+                Line::Row(_) => rows += score,
+                Line::Column(_) => columns += score,
+            }
+        }
+        (rows, columns)
+    }
+}
+
+#[test]
+fn test_cross_grid() {
+    let deck = Deck::new();
+    let grid = CrossGrid::deal(&deck);
+    // An unshuffled deck deals aces through sevens across the rows, so each column is nearly a run.
+    assert_eq!(grid.line(Line::Row(0)).iter().map(Card::to_string).collect::<Vec<_>>(),
+        vec!["AS", "AC", "AH", "AD", "2S"]);
+    let column: Vec<i8> = grid.line(Line::Column(0)).iter().map(|card| card.number).collect();
+    assert_eq!(column, vec![1, 2, 3, 4, 6]);
+    assert_eq!(Line::all().len(), 10);
+    assert_eq!(format!("{}", Line::Column(2)), "column 3");
+    assert_eq!(grid.crib().held_cards().len(), 4);
+
+    // The first row is four aces and a two: six pairs, and no fifteens or runs.
+    let (rows, columns) = grid.totals();
+    assert!(rows > 0 && columns > 0);
+    assert_eq!(Hand::new(&grid.line(Line::Row(0))).score(), 12);
+}
@@ -3,21 +3,30 @@
 // Copyright (c) 2016 by William R. Fraser
 //
 
+use rand::Rng;
 use rand::seq::SliceRandom;
 
 use crate::card::{Card, Suit};
 use crate::hand::Hand;
+use crate::rules::Rules;
 
+#[derive(Clone)]
 pub struct Deck {
     cards: Vec<Card>,
 }
 
+impl Default for Deck {
+    fn default() -> Deck {
+        Deck::new()
+    }
+}
+
 impl<'a> Deck {
     pub fn new() -> Deck {
         let mut cards = vec![];
 
         for number in 1 ..= 13 {
-            for suit in &[Suit::Spades, Suit::Clubs, Suit::Hearts, Suit::Diamonds] {
+            for suit in &Suit::ALL {
                 cards.push( Card {
                     number,
                     suit: *suit,
@@ -31,13 +40,114 @@ impl<'a> Deck {
         }
     }
 
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn shuffle(&mut self) {
-        let mut rng = rand::thread_rng();
-        self.cards.shuffle(&mut rng);
+        self.shuffle_with(&mut rand::thread_rng());
+    }
+
+    /// Shuffles with the given random number generator, so a seeded one gives the same order every
+    /// time.
+    pub fn shuffle_with<R: Rng>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
+    /// Deals a hand of the given size, plus a starter card.
     pub fn deal_hand(&'a self, size: usize) -> Hand<'a> {
-        Hand::new(&self.cards[0..size])
+        Hand::with_starter(&self.cards[0 ..= size])
+    }
+
+    /// Deals a round for everyone at the table. Each player throws the first cards they were dealt
+    /// into the crib.
+    pub fn deal_round(&self, rules: &Rules) -> Round {
+        let starter = self.cards[rules.cards_used() - 1];
+        let mut crib = vec![];
+        let mut shows = vec![];
+        for dealt in self.cards[.. rules.players * rules.deal].chunks(rules.deal) {
+            crib.extend_from_slice(&dealt[.. rules.discard]);
+            let mut show = dealt[rules.discard ..].to_vec();
+            show.push(starter);
+            shows.push(show);
+        }
+        let from_deck = rules.players * rules.deal;
+        crib.extend_from_slice(&self.cards[from_deck .. from_deck + rules.crib_from_deck]);
+        crib.push(starter);
+        Round { shows, crib }
+    }
+}
+
+/// The cards for one round: what each player kept, and the crib, each followed by the starter.
+pub struct Round {
+    pub shows: Vec<Vec<Card>>,
+    pub crib: Vec<Card>,
+}
+
+impl Round {
+    pub fn hand(&self, player: usize) -> Hand<'_> {
+        Hand::with_starter(&self.shows[player])
     }
 
+    pub fn crib(&self) -> Hand<'_> {
+        Hand::crib(&self.crib)
+    }
+
+    pub fn starter(&self) -> &Card {
+        self.crib.last().unwrap()
+    }
+
+    /// The cards a player was dealt: what they threw into the crib, then what they kept.
+    pub fn dealt(&self, player: usize, rules: &Rules) -> Vec<Card> {
+        let mut dealt = self.crib[player * rules.discard .. (player + 1) * rules.discard].to_vec();
+        let show = &self.shows[player];
+        dealt.extend_from_slice(&show[.. show.len() - 1]);
+        dealt
+    }
+
+    /// Has a player keep and throw different cards out of the ones they were dealt.
+    pub fn rethrow(&mut self, player: usize, rules: &Rules, kept: &[Card], thrown: &[Card]) {
+        let starter = *self.starter();
+        self.crib[player * rules.discard .. (player + 1) * rules.discard].copy_from_slice(thrown);
+        self.shows[player] = kept.to_vec();
+        self.shows[player].push(starter);
+    }
+}
+
+#[test]
+fn test_deal_round() {
+    let deck = Deck::new();
+    for rules in &Rules::ALL {
+        let round = deck.deal_round(rules);
+        assert_eq!(round.shows.len(), rules.players);
+        let mut seen: Vec<&Card> = vec![round.starter()];
+        for player in 0 .. rules.players {
+            let hand = round.hand(player);
+            assert_eq!(hand.held_cards().len(), rules.hand_size());
+            assert_eq!(hand.starter, Some(round.starter()));
+            seen.extend(hand.held_cards());
+        }
+        let crib = round.crib();
+        assert!(crib.crib);
+        assert_eq!(crib.held_cards().len(), rules.crib_size());
+        seen.extend(crib.held_cards());
+        for (i, card) in seen.iter().enumerate() {
+            assert!(!seen[.. i].contains(card), "{} was dealt twice under {} rules", card, rules);
+        }
+        assert_eq!(seen.len(), rules.cards_used());
+    }
+}
+
+#[test]
+fn test_rethrow() {
+    let deck = Deck::new();
+    let rules = Rules::STANDARD;
+    let mut round = deck.deal_round(&rules);
+    let dealt = round.dealt(1, &rules);
+    assert_eq!(dealt, deck.cards()[rules.deal .. 2 * rules.deal].to_vec());
+
+    round.rethrow(1, &rules, &dealt[.. 4], &dealt[4 ..]);
+    assert_eq!(round.hand(1).held_cards(), &dealt[.. 4]);
+    assert_eq!(&round.crib[2 .. 4], &dealt[4 ..]);
+    assert_eq!(round.dealt(0, &rules), deck.cards()[.. rules.deal].to_vec());
 }
@@ -0,0 +1,270 @@
+// Discard :: ways of deciding which cards to throw into the crib.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// Each strategy puts a value on keeping some of the cards dealt and throwing the rest; every way of
+// splitting the deal is then ranked by it. The expected values treat every card not dealt to you as
+// equally likely to be the starter, or to be thrown into the crib by someone else.
+//
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::card::{Card, Suit};
+use crate::deck::Deck;
+use crate::hand::Hand;
+use crate::packed::{self, CardSet};
+use crate::rules::Rules;
+use crate::starters;
+use crate::util;
+
+pub trait DiscardStrategy {
+    fn name(&self) -> &'static str;
+
+    /// How good it is to keep `kept` and throw `thrown` into the crib.
+    fn value(&mut self, kept: &[Card], thrown: &[Card], dealer: bool) -> Value;
+}
+
+/// What a strategy makes of one way of splitting the deal.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Value {
+    /// Only the order matters: higher is better.
+    pub total: f64,
+    /// The hand's expected score, if the strategy worked it out.
+    pub hand: Option<f64>,
+    /// The crib's expected score, negative when it's the opponent's, if the strategy worked it out.
+    pub crib: Option<f64>,
+}
+
+impl Value {
+    fn total(total: f64) -> Value {
+        Value { total, .. Value::default() }
+    }
+}
+
+/// Throws cards at random.
+pub struct RandomDiscard {
+    rng: StdRng,
+}
+
+impl RandomDiscard {
+    pub fn new(seed: u64) -> RandomDiscard {
+        RandomDiscard { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl DiscardStrategy for RandomDiscard {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn value(&mut self, _kept: &[Card], _thrown: &[Card], _dealer: bool) -> Value {
+        Value::total(self.rng.gen())
+    }
+}
+
+/// Keeps the most points before the starter is cut.
+pub struct GuaranteedPoints;
+
+impl DiscardStrategy for GuaranteedPoints {
+    fn name(&self) -> &'static str {
+        "guaranteed"
+    }
+
+    fn value(&mut self, kept: &[Card], _thrown: &[Card], _dealer: bool) -> Value {
+        // Without a starter, a hand only counts a flush of five, but a flush of all the cards kept
+        // counts whatever gets cut.
+        let mut points = Hand::new(kept).score();
+        if kept.len() < 5 && kept.iter().all(|card| card.suit == kept[0].suit) {
+            points += kept.len() as i32;
+        }
+        Value::total(f64::from(points))
+    }
+}
+
+/// Keeps the most points on average over every starter.
+pub struct HandEv;
+
+impl DiscardStrategy for HandEv {
+    fn name(&self) -> &'static str {
+        "hand-ev"
+    }
+
+    fn value(&mut self, kept: &[Card], thrown: &[Card], _dealer: bool) -> Value {
+        let hand = hand_ev(kept, thrown);
+        Value { total: hand, hand: Some(hand), crib: None }
+    }
+}
+
+/// Like `HandEv`, but adds what the cards thrown are expected to make in the crib when it's yours,
+/// and takes it away when it's your opponent's.
+pub struct HandAndCribEv {
+    /// How many cards go into the crib from elsewhere: other players' throws, and the deck.
+    others: usize,
+}
+
+impl HandAndCribEv {
+    pub fn new(rules: &Rules) -> HandAndCribEv {
+        HandAndCribEv { others: rules.crib_size() - rules.discard }
+    }
+}
+
+impl DiscardStrategy for HandAndCribEv {
+    fn name(&self) -> &'static str {
+        "crib-ev"
+    }
+
+    fn value(&mut self, kept: &[Card], thrown: &[Card], dealer: bool) -> Value {
+        let hand = hand_ev(kept, thrown);
+        let crib = crib_ev(thrown, kept, self.others) * if dealer { 1. } else { -1. };
+        Value { total: hand + crib, hand: Some(hand), crib: Some(crib) }
+    }
+}
+
+pub const STRATEGY_NAMES: [&str; 4] = ["random", "guaranteed", "hand-ev", "crib-ev"];
+
+pub fn strategy_from_name(name: &str, rules: &Rules) -> Option<Box<dyn DiscardStrategy>> {
+    match name {
+        "random" => Some(Box::new(RandomDiscard::new(rand::thread_rng().gen()))),
+        "guaranteed" => Some(Box::new(GuaranteedPoints)),
+        "hand-ev" => Some(Box::new(HandEv)),
+        "crib-ev" => Some(Box::new(HandAndCribEv::new(rules))),
+        _ => None,
+    }
+}
+
+/// The expected score of the cards kept, over every starter but the cards thrown.
+pub fn hand_ev(kept: &[Card], thrown: &[Card]) -> f64 {
+    starters::summarize(&starters::starter_scores(kept, thrown, false)).mean
+}
+
+/// The expected score of a crib with the cards thrown in it, filled out with `others` more cards
+/// and a starter from the cards not dealt to you.
+pub fn crib_ev(thrown: &[Card], kept: &[Card], others: usize) -> f64 {
+    let unseen: Vec<Card> = Deck::new().cards().iter()
+        .filter(|card| !thrown.contains(card) && !kept.contains(card))
+        .cloned()
+        .collect();
+    let mut total = 0u64;
+    let mut count = 0u64;
+    for_each_combination(&unseen, others, CardSet::from_cards(thrown), &mut |crib| {
+        for starter in &unseen {
+            if !crib.contains(starter) {
+                total += u64::from(crib_score(crib, starter));
+                count += 1;
+            }
+        }
+    });
+    total as f64 / count as f64
+}
+
+/// The packed scorer counts a four-card flush the way a hand does; in the crib, it has to include
+/// the starter.
+fn crib_score(crib: CardSet, starter: &Card) -> u8 {
+    let score = packed::score(crib, starter);
+    let short_flush = Suit::ALL.iter()
+        .any(|&suit| suit != starter.suit && crib.suit_numbers(suit).count_ones() as usize == crib.len());
+    if short_flush { score - crib.len() as u8 } else { score }
+}
+
+/// Calls `f` with `set` plus each way of choosing `k` of the cards.
+fn for_each_combination<F: FnMut(CardSet)>(cards: &[Card], k: usize, set: CardSet, f: &mut F) {
+    if k == 0 {
+        return f(set);
+    }
+    for (i, card) in cards.iter().enumerate().take(cards.len() + 1 - k) {
+        let mut with = set;
+        with.insert(card);
+        for_each_combination(&cards[i + 1 ..], k - 1, with, f);
+    }
+}
+
+/// One way of splitting the cards dealt.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Discard {
+    pub kept: Vec<Card>,
+    pub thrown: Vec<Card>,
+    pub value: Value,
+}
+
+/// Every way to throw `discard` of the cards dealt, best first according to the strategy.
+pub fn rank<S: DiscardStrategy + ?Sized>(strategy: &mut S, dealt: &[Card], discard: usize, dealer: bool)
+        -> Vec<Discard> {
+    let mut options: Vec<Discard> = util::power_set(dealt)
+        .filter(|thrown| thrown.len() == discard)
+        .map(|thrown| {
+            let thrown: Vec<Card> = thrown.into_iter().cloned().collect();
+            let kept: Vec<Card> = dealt.iter().filter(|card| !thrown.contains(card)).cloned().collect();
+            let value = strategy.value(&kept, &thrown, dealer);
+            Discard { kept, thrown, value }
+        })
+        .collect();
+    options.sort_by(|a, b| b.value.total.partial_cmp(&a.value.total).unwrap());
+    options
+}
+
+/// The best way to throw `discard` of the cards dealt, according to the strategy.
+pub fn choose<S: DiscardStrategy + ?Sized>(strategy: &mut S, dealt: &[Card], discard: usize, dealer: bool)
+        -> Discard {
+    rank(strategy, dealt, discard, dealer).remove(0)
+}
+
+#[test]
+fn test_strategies() {
+    use crate::card::parse_cards;
+
+    let dealt = parse_cards("5h 5d 4s 6c 10c kd");
+
+    let ranked = rank(&mut GuaranteedPoints, &dealt, 2, true);
+    assert_eq!(ranked.len(), 15);
+    assert_eq!(ranked[0].kept, parse_cards("5h 5d 4s 6c"));
+    assert_eq!(ranked[0].value, Value::total(12.));
+    assert!(ranked.windows(2).all(|pair| pair[0].value.total >= pair[1].value.total));
+
+    // A flush kept is there whatever the starter, and so is three of a suit kept in five-card.
+    let flush = parse_cards("2h 4h 8h qh");
+    assert_eq!(GuaranteedPoints.value(&flush, &parse_cards("ks 3c"), true), Value::total(4.));
+    let flush = parse_cards("2h 4h 8h");
+    assert_eq!(GuaranteedPoints.value(&flush, &parse_cards("ks 3c"), true), Value::total(3.));
+
+    let best = choose(&mut HandEv, &dealt, 2, true);
+    assert_eq!(best.kept, parse_cards("5h 5d 4s 6c"));
+    assert!((best.value.total - hand_ev(&best.kept, &best.thrown)).abs() < 1e-9);
+    assert_eq!(best.value.hand, Some(best.value.total));
+
+    // Counting the hand alone, the jack and queen go. The dealer would rather give their own crib
+    // the 7-8, and the pone would rather not give the dealer the jack.
+    let dealt = parse_cards("ah 2d 7s 8c jh qd");
+    assert_eq!(choose(&mut HandEv, &dealt, 2, true).thrown, parse_cards("jh qd"));
+    let mut strategy = HandAndCribEv::new(&Rules::STANDARD);
+    assert_eq!(choose(&mut strategy, &dealt, 2, true).thrown, parse_cards("7s 8c"));
+    let best = choose(&mut strategy, &dealt, 2, false);
+    assert_eq!(best.thrown, parse_cards("ah qd"));
+    let (hand, crib) = (best.value.hand.unwrap(), best.value.crib.unwrap());
+    assert!(crib < 0. && (hand + crib - best.value.total).abs() < 1e-9);
+
+    let mut random = RandomDiscard::new(1);
+    let first = rank(&mut random, &dealt, 2, true);
+    assert_eq!(rank(&mut RandomDiscard::new(1), &dealt, 2, true), first);
+    for name in &STRATEGY_NAMES {
+        assert_eq!(strategy_from_name(name, &Rules::STANDARD).unwrap().name(), *name);
+    }
+}
+
+#[test]
+fn test_crib_ev() {
+    use crate::card::parse_cards;
+
+    // The crib scorer agrees with the hand scorer's crib rules.
+    let cards = parse_cards("2h 6h 9h kh");
+    for starter in Deck::new().cards().iter().filter(|card| !cards.contains(card)) {
+        let mut show = cards.clone();
+        show.push(*starter);
+        assert_eq!(i32::from(crib_score(CardSet::from_cards(&cards), starter)), Hand::crib(&show).score());
+    }
+
+    let thrown = parse_cards("5h 5d");
+    let kept = parse_cards("9s 9c 2h kd");
+    let ev = crib_ev(&thrown, &kept, 2);
+    assert!(ev > 7. && ev < 10., "{}", ev);
+}
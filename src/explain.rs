@@ -0,0 +1,207 @@
+// Explain :: describes why combos score, and why guesses don't.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use crate::card::{self, Card};
+use crate::combo::{Combo, ComboKind};
+use crate::hand::Hand;
+use crate::util;
+
+/// Explains why a combo scores, e.g. "5 + 10 = 15" for a fifteen.
+pub fn explain_combo(combo: &Combo<'_>, hand: &Hand<'_>) -> String {
+    match combo.kind {
+        ComboKind::Fifteen => format!("{} = 15", sum_str(combo.cards.iter().cloned())),
+        ComboKind::Pair => format!("a pair of {}", card::number_plural(combo.cards[0].number)),
+        ComboKind::PairRoyal | ComboKind::DoublePairRoyal => {
+            let n = combo.cards.len();
+            format!("{} {} make {} pairs",
+                count_name(n), card::number_plural(combo.cards[0].number),
+                util::binomial_coefficient(n as i8, 2))
+        },
+        ComboKind::Run(_) => format!("consecutive numbers {}", run_str(&combo.cards)),
+        ComboKind::Flush(n) => {
+            let suit = combo.cards[0].suit.name();
+            match hand.starter {
+                Some(_) if hand.crib => format!("all the cards in the crib are {}, including the starter", suit),
+                Some(_) if n > hand.held_cards().len() => {
+                    format!("all the cards in the hand are {}, and so is the starter", suit)
+                },
+                Some(_) => format!("all the cards in the hand are {}", suit),
+                None => format!("all {} cards are {}", n, suit),
+            }
+        },
+        ComboKind::Nobs => {
+            format!("the jack of {} is the same suit as the starter", combo.cards[0].suit.name())
+        },
+        ComboKind::Heels => "the starter is a jack, and goes to the dealer".to_owned(),
+        ComboKind::DoubleRun(_) | ComboKind::TripleRun(_) | ComboKind::DoubleDoubleRun(_) => {
+            explain_compound_run(combo)
+        },
+    }
+}
+
+fn explain_compound_run(combo: &Combo<'_>) -> String {
+    let by_number = util::group_by(&combo.cards, |card| card.number);
+    let numbers: Vec<i8> = by_number.keys().cloned().collect();
+    let runs = by_number.values().fold(1, |runs, group| runs * group.len());
+    let pairs: Vec<String> = by_number.iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|(number, group)| {
+            let name = if group.len() == 2 { "pair" } else { "pair royal" };
+            format!("the {} of {}", name, card::number_plural(*number))
+        })
+        .collect();
+    let run: Vec<String> = numbers.iter().map(|&number| card::number_symbol(number)).collect();
+    format!("{} {} different ways, plus {}", run.join("-"), count_name(runs), pairs.join(" and "))
+}
+
+/// Explains why the guessed cards don't make any of the combos left in the hand. Each reason is
+/// one sentence.
+pub fn explain_rejection(hand: &Hand<'_>, cards: &[Card], claimed: &[Combo<'_>]) -> Vec<String> {
+    if claimed.iter().any(|combo| combo == cards) {
+        return vec!["You already counted that.".to_owned()];
+    }
+
+    if cards.len() == 1 {
+        let card = &cards[0];
+        return vec![match hand.starter {
+            Some(starter) if card.number == 11 && card == starter => {
+                "The starter doesn't count for nobs; it has to be a jack in your hand.".to_owned()
+            },
+            Some(starter) if card.number == 11 => {
+                format!("For nobs, the jack has to be {}, like the starter.", starter.suit.name())
+            },
+            _ => "One card on its own doesn't score anything.".to_owned(),
+        }];
+    }
+
+    let mut reasons = vec![];
+
+    let sum: i8 = cards.iter().map(Card::value).sum();
+    reasons.push(format!("{} = {}, not 15.", sum_str(cards.iter()), sum));
+
+    if cards.len() <= 4 && cards.iter().any(|card| card.number != cards[0].number) {
+        reasons.push("They aren't all the same number, so they aren't a pair.".to_owned());
+    }
+
+    if cards.len() >= 3 {
+        let by_number = util::group_by(cards, |card| card.number);
+        if let Some(group) = by_number.values().find(|group| group.len() > 1) {
+            reasons.push(format!("A run can't have two {}.", card::number_plural(group[0].number)));
+        } else {
+            let numbers: Vec<i8> = by_number.keys().cloned().collect();
+            let missing: Vec<String> = (numbers[0] ..= numbers[numbers.len() - 1])
+                .filter(|number| !numbers.contains(number))
+                .map(card::number_symbol)
+                .collect();
+            if !missing.is_empty() {
+                let sorted: Vec<&Card> = by_number.values().map(|group| group[0]).collect();
+                reasons.push(format!("{} isn't a run: it's missing {}.",
+                    run_str(&sorted), missing.join(", ")));
+            }
+        }
+    }
+
+    if cards.len() >= 3 && cards.iter().all(|card| card.suit == cards[0].suit) {
+        let held = hand.held_cards();
+        if let Some(starter) = hand.starter {
+            if hand.crib && !cards.contains(starter) && held.iter().all(|card| cards.contains(card)) {
+                reasons.push("In the crib, a flush has to include the starter too.".to_owned());
+            } else if cards.contains(starter) && !held.iter().all(|card| cards.contains(card)) {
+                reasons.push("A flush has to have all the cards in the hand; the starter can only add to it."
+                    .to_owned());
+            }
+        }
+    }
+
+    reasons
+}
+
+fn sum_str<'a, I: Iterator<Item = &'a Card>>(cards: I) -> String {
+    let values: Vec<String> = cards.map(|card| card.value().to_string()).collect();
+    values.join(" + ")
+}
+
+fn run_str(cards: &[&Card]) -> String {
+    let mut numbers: Vec<i8> = cards.iter().map(|card| card.number).collect();
+    numbers.sort();
+    let symbols: Vec<String> = numbers.into_iter().map(card::number_symbol).collect();
+    symbols.join("-")
+}
+
+fn count_name(n: usize) -> &'static str {
+    match n {
+        2 => "two",
+        3 => "three",
+        4 => "four",
+        _ => "many",
+    }
+}
+
+#[test]
+fn test_explain_combo() {
+    use crate::card::parse_cards;
+    use crate::combo;
+
+    let cards = parse_cards("3s 4s 5h 5d jd");
+    let hand = Hand::with_starter(&cards);
+    let explanations: Vec<String> = hand.find_all_combos().iter()
+        .map(|combo| explain_combo(combo, &hand))
+        .collect();
+    assert_eq!(explanations, vec![
+        "5 + 10 = 15",
+        "5 + 10 = 15",
+        "a pair of fives",
+        "consecutive numbers 3-4-5",
+        "consecutive numbers 3-4-5",
+    ]);
+
+    let compound = combo::combine_compounds(hand.find_all_combos());
+    assert_eq!(explain_combo(compound.last().unwrap(), &hand),
+        "3-4-5 two different ways, plus the pair of fives");
+
+    let cards = parse_cards("jh 2h 6h 9h 3h");
+    let hand = Hand::with_starter(&cards);
+    let explanations: Vec<String> = hand.find_all_combos().iter()
+        .filter(|combo| combo.kind != ComboKind::Fifteen)
+        .map(|combo| explain_combo(combo, &hand))
+        .collect();
+    assert_eq!(explanations, vec![
+        "all the cards in the hand are hearts, and so is the starter",
+        "the jack of hearts is the same suit as the starter",
+    ]);
+}
+
+#[test]
+fn test_explain_rejection() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d js 7s jh");
+    let hand = Hand::with_starter(&cards);
+
+    assert_eq!(explain_rejection(&hand, &parse_cards("5h 5d js"), &[]), vec![
+        "5 + 5 + 10 = 20, not 15.",
+        "They aren't all the same number, so they aren't a pair.",
+        "A run can't have two fives.",
+    ]);
+    assert_eq!(explain_rejection(&hand, &parse_cards("5h 7s js"), &[]), vec![
+        "5 + 7 + 10 = 22, not 15.",
+        "They aren't all the same number, so they aren't a pair.",
+        "5-7-J isn't a run: it's missing 6, 8, 9, 10.",
+    ]);
+    assert_eq!(explain_rejection(&hand, &parse_cards("js"), &[]), vec![
+        "For nobs, the jack has to be hearts, like the starter.",
+    ]);
+    assert_eq!(explain_rejection(&hand, &parse_cards("jh"), &[]), vec![
+        "The starter doesn't count for nobs; it has to be a jack in your hand.",
+    ]);
+
+    let combos = hand.find_all_combos();
+    assert_eq!(explain_rejection(&hand, &parse_cards("5d 5h"), &combos), vec!["You already counted that."]);
+
+    let cards = parse_cards("2h 4h 8h qh ks");
+    let crib = Hand::crib(&cards);
+    assert_eq!(explain_rejection(&crib, &parse_cards("2h 4h 8h qh"), &[]).last().unwrap(),
+        "In the crib, a flush has to include the starter too.");
+}
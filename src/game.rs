@@ -5,59 +5,1399 @@
 
 use std::cell::RefCell;
 
-use crate::deck::Deck;
-use crate::ui::UserInterface;
+use rand::Rng;
+
+use crate::card::Card;
+use crate::catch;
+use crate::choice;
+use crate::combo::{self, Category, Combo, ComboKind, Compound};
+use crate::cross::{CrossGrid, Line};
+use crate::discard;
+use crate::deck::{Deck, Round};
+use crate::explain;
+use crate::generate::Generator;
+use crate::hand::Hand;
+use crate::lessons::Unit;
+use crate::rules::Rules;
+use crate::spoken;
+use crate::starters;
+use crate::stats::Stats;
+use crate::ui::{Action, Guess, Hint, UserInterface};
+#[cfg(test)]
+use crate::catch::Verdict;
+#[cfg(test)]
+use crate::choice::Question;
+#[cfg(test)]
+use crate::spoken::{ComponentResult, SpokenCount};
+#[cfg(test)]
+use crate::ui::CategoryClaim;
+#[cfg(test)]
+use crate::starters::StarterQuestion;
+use crate::util;
 
 const SCORE_BAD_GUESS_WRONG_SCORE: i32 = 1;
 const SCORE_BAD_GUESS_INVALID_COMBO: i32 = 2;
+const SCORE_BAD_MUGGINS_CALL: i32 = 2;
+const SCORE_STARTER_QUIZ: i32 = 2;
+
+/// How many deals to pick the richest hand from, in a drill.
+const DRILL_DEALS: usize = 8;
+
+/// How the player announces the points in their hand.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GuessStyle {
+    /// Name the cards of each combo along with its score.
+    Cards,
+    /// Announce a total for each category, e.g. "fifteens 6, pairs 2".
+    Categories,
+    /// Pick the right total out of four, or the combo that doesn't score out of four. For
+    /// beginners.
+    MultipleChoice,
+    /// Count the way it's said out loud, e.g. "fifteen two, fifteen four, and a pair is six".
+    Spoken,
+}
+
+/// How three or four of a kind are presented. Either way, the player may claim them as a group
+/// or pair by pair.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PairStyle {
+    /// One combo for the whole group, e.g. a pair royal for 6.
+    Grouped,
+    /// One combo for each two cards of the group, e.g. three pairs for 2 each.
+    Individual,
+}
+
+/// Which game is being played.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    /// Count your hand, and the crib when it's yours.
+    Normal,
+    /// The lowest score wins. The computer counts its own hand too, and anything you miss or get
+    /// wrong is pegged against you instead of going to the computer.
+    Lowball,
+    /// Count every row and column of a 5x5 grid of cards, and then the crib.
+    CrossCribbage,
+    /// The computer counts its own hand, sometimes wrongly, and the player calls muggins on any
+    /// mistakes.
+    CatchTheComputer,
+    /// Look at the cards kept, and say which starters would help them most.
+    BestStarter,
+}
+
+pub struct Settings {
+    pub rules: Rules,
+    pub mode: Mode,
+    pub guess_style: GuessStyle,
+    pub pair_style: PairStyle,
+    /// Show missed runs and their pairs together as double runs, triple runs, and double double
+    /// runs. Compound runs can always be claimed in one go, regardless of this setting.
+    pub compound_runs: bool,
+    /// What each hint in a hand costs, in order. The last one is the cost of any more after that.
+    pub hint_costs: Vec<i32>,
+    /// How often the computer miscounts its hand when you're trying to catch it out.
+    pub cpu_error_rate: f64,
+    /// The chance that the computer notices each combo the player missed, and takes it as muggins.
+    pub muggins_skill: f64,
+    /// Only count combos of this category, and deal hands with plenty of them.
+    pub drill: Option<Category>,
+    /// Deal hands with more of the combos the player tends to miss, and more so the better they
+    /// get.
+    pub adaptive: bool,
+    /// The discard strategy the computer throws its cards into the crib with, by name. Without one,
+    /// it throws the first cards it's dealt.
+    pub cpu_discard: Option<&'static str>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            rules: Rules::default(),
+            mode: Mode::Normal,
+            guess_style: GuessStyle::Cards,
+            pair_style: PairStyle::Grouped,
+            compound_runs: false,
+            hint_costs: vec![1, 1, 2],
+            cpu_error_rate: 0.5,
+            muggins_skill: 1.,
+            drill: None,
+            adaptive: false,
+            cpu_discard: None,
+        }
+    }
+}
+
+/// A guess the player made, and everything needed to take it back.
+struct Claim<'h> {
+    guess: Guess,
+    combos_before: Vec<Combo<'h>>,
+    claimed_before: usize,
+    player_points: i32,
+    /// Whether it wasn't a combo at all. That's most likely a typo, so taking it back gives back
+    /// the penalty too.
+    invalid: bool,
+}
 
 pub struct Game<'a, UI: UserInterface> {
     ui: &'a RefCell<UI>,
+    settings: Settings,
+    /// Hands played so far this game. The computer deals first, and the deal passes to the left,
+    /// with the player sitting on the computer's left.
+    hands_played: usize,
+    /// Multiple-choice questions asked, and how many were answered right.
+    questions_asked: usize,
+    questions_right: usize,
+    /// What the player has missed, in this session and any before it.
+    stats: Stats,
 }
 
 impl<'a, UI: UserInterface> Game<'a, UI> {
-    pub fn new(rc_ui: &'a RefCell<UI>) -> Game<'a, UI> {
+    pub fn new(rc_ui: &'a RefCell<UI>, settings: Settings) -> Game<'a, UI> {
         Game {
             ui: rc_ui,
+            settings,
+            hands_played: 0,
+            questions_asked: 0,
+            questions_right: 0,
+            stats: Stats::default(),
         }
     }
 
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Picks up the record of what the player misses from a previous session.
+    pub fn set_stats(&mut self, stats: Stats) {
+        self.stats = stats;
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// How many multiple-choice questions were answered right, out of how many.
+    pub fn accuracy(&self) -> (usize, usize) {
+        (self.questions_right, self.questions_asked)
+    }
+
+    /// Starts over with the computer dealing.
+    pub fn new_game(&mut self) {
+        self.hands_played = 0;
+    }
+
+    /// Deals a round and plays the player's hand, and then the crib if it's theirs.
     pub fn play(&mut self, deck: &Deck) {
+        match self.settings.mode {
+            Mode::CrossCribbage => return self.play_cross(deck),
+            Mode::CatchTheComputer => {
+                let round = deck.deal_round(&self.settings.rules);
+                return self.play_catch(&round.hand(1));
+            },
+            Mode::BestStarter => {
+                let round = deck.deal_round(&self.settings.rules);
+                self.play_starter_quiz(round.hand(0).held_cards());
+                return;
+            },
+            Mode::Normal | Mode::Lowball => (),
+        }
+
+        let rules = self.settings.rules;
+        if self.hands_played == 0 && rules.pone_bonus > 0 {
+            let mut ui = self.ui.borrow_mut();
+            ui.display_pone_bonus(rules.pone_bonus);
+            ui.add_score_player(rules.pone_bonus);
+        }
+
+        let mut round = match self.settings.drill {
+            Some(category) => deal_drill(deck, &rules, category, &mut rand::thread_rng()),
+            None if self.settings.adaptive => {
+                let stats = &self.stats;
+                deal_best(deck, &rules, stats.adaptive_deals(), &mut rand::thread_rng(), |hand| stats.weakness(hand))
+            },
+            None => deck.deal_round(&rules),
+        };
+        if let Some(name) = self.settings.cpu_discard {
+            self.computer_discards(&mut round, name);
+        }
+        self.play_hand(&round.hand(0));
+        if self.player_deals() {
+            self.play_hand(&round.crib());
+        }
+        if self.settings.mode == Mode::Lowball {
+            let hand = round.hand(1);
+            let score = hand.score();
+            let mut ui = self.ui.borrow_mut();
+            ui.display_cpu_hand(&hand, score);
+            ui.add_score_cpu(score);
+        }
+        self.hands_played += 1;
+    }
+
+    /// Whether the crib is the player's this hand. With more than two players, the computer
+    /// stands in for everyone else, so the player deals once every time around the table.
+    fn player_deals(&self) -> bool {
+        self.dealer() == 0
+    }
+
+    /// Who deals this hand: the player is 0, and the computer's seats follow.
+    fn dealer(&self) -> usize {
+        (self.hands_played + 1) % self.settings.rules.players
+    }
+
+    /// Each of the computer's seats throws the cards the strategy likes best, or in lowball, the
+    /// ones it likes least.
+    fn computer_discards(&self, round: &mut Round, name: &str) {
+        let rules = self.settings.rules;
+        let mut strategy = discard::strategy_from_name(name, &rules).unwrap();
+        for player in 1 .. rules.players {
+            let dealt = round.dealt(player, &rules);
+            let options = discard::rank(&mut *strategy, &dealt, rules.discard, player == self.dealer());
+            let choice = if self.settings.mode == Mode::Lowball { options.last() } else { options.first() };
+            let choice = choice.unwrap();
+            round.rethrow(player, &rules, &choice.kept, &choice.thrown);
+        }
+    }
+
+    fn play_cross(&mut self, deck: &Deck) {
+        let grid = CrossGrid::deal(deck);
+        for line in Line::all() {
+            self.ui.borrow_mut().display_cross(&grid, Some(line));
+            self.play_hand(&Hand::new(&grid.line(line)));
+        }
+        self.ui.borrow_mut().display_cross(&grid, None);
+        self.play_hand(&grid.crib());
+    }
+
+    /// The computer announces its count, and the player either accepts it or calls out the combos
+    /// it got wrong. The computer pegs what it announced, less anything bogus that was caught, and
+    /// the player takes anything it missed as muggins.
+    pub fn play_catch(&mut self, hand: &Hand<'_>) {
         let mut ui = self.ui.borrow_mut();
+        let announced = catch::announce(hand, self.settings.cpu_error_rate, &mut rand::thread_rng());
+        ui.display_cpu_announcement(hand, &announced);
+        let calls = ui.get_muggins_calls(hand);
+
+        let actual = hand.find_all_combos();
+        let verdict = catch::judge(catch::check(&actual, &announced), &calls);
+        ui.display_verdict(hand, &verdict);
+
+        let total = |combos: &[Combo<'_>]| combos.iter().fold(0, |score, combo| score + i32::from(combo.score));
+        ui.add_score_cpu(total(&announced) - total(&verdict.caught_bogus)
+            + SCORE_BAD_MUGGINS_CALL * verdict.wrong_calls.len() as i32);
+        ui.add_score_player(total(&verdict.caught_missed));
+    }
+
+    /// Asks which starters would score the most with the cards kept, or which would give at least
+    /// some number of points, returning whether the player got it right.
+    pub fn play_starter_quiz(&mut self, held: &[Card]) -> bool {
+        let mut ui = self.ui.borrow_mut();
+        let numbers = starters::by_number(&starters::starter_scores(held, &[], false));
+        let question = starters::ask(&numbers, &mut rand::thread_rng());
+        let chosen = ui.get_starter_answer(held, &question);
+        let right = question.is_right(&numbers, &chosen);
+        ui.display_starter_result(held, &question, &numbers, right);
+
+        self.questions_asked += 1;
+        if right {
+            self.questions_right += 1;
+            ui.add_score_player(SCORE_STARTER_QUIZ);
+        } else {
+            self.award_cpu(&mut *ui, SCORE_STARTER_QUIZ);
+        }
+        right
+    }
 
-        let hand = deck.deal_hand(5);
-        ui.display_hand(&hand);
+    /// Explains a lesson unit and deals its exercises, returning how many the player counted
+    /// perfectly. Only the unit's category counts while it's going.
+    pub fn play_lesson(&mut self, unit: &Unit, generator: &mut Generator) -> usize {
+        self.ui.borrow_mut().display_lesson(unit);
+        let drill = std::mem::replace(&mut self.settings.drill, unit.drill);
+        let mut perfect = 0;
+        for _ in 0 .. unit.exercises {
+            let cards = match generator.hand(unit.constraints) {
+                Some(cards) => cards,
+                None => break,
+            };
+            if self.play_hand(&Hand::with_starter(&cards)) {
+                perfect += 1;
+            }
+        }
+        self.settings.drill = drill;
+        self.ui.borrow_mut().display_lesson_result(unit, perfect);
+        perfect
+    }
+
+    /// Plays one hand, returning whether the player counted all of it without any wrong guesses or
+    /// hints, even ones taken back.
+    pub fn play_hand(&mut self, hand: &Hand<'_>) -> bool {
+        let mut ui = self.ui.borrow_mut();
+        ui.display_hand(hand);
 
         let mut combos = hand.find_all_combos();
+        if self.settings.pair_style == PairStyle::Individual {
+            combos = combo::split_pairs(combos);
+        }
+        if let Some(category) = self.settings.drill {
+            combos.retain(|combo| combo.category() == category);
+        }
+
+        match self.settings.guess_style {
+            GuessStyle::Cards => self.play_cards(&mut *ui, hand, combos),
+            GuessStyle::Categories => self.play_categories(&mut *ui, hand, &combos),
+            GuessStyle::Spoken => self.play_spoken(&mut *ui, hand, &combos),
+            GuessStyle::MultipleChoice => self.play_choice(&mut *ui, hand),
+        }
+    }
+
+    /// Getting it right scores the hand for the player; getting it wrong gives it to the computer.
+    fn play_choice(&mut self, ui: &mut UI, hand: &Hand<'_>) -> bool {
+        let question = choice::ask(hand, &mut rand::thread_rng());
+        let chosen = ui.get_choice(hand, &question);
+        ui.display_choice_result(hand, &question, chosen);
 
+        self.questions_asked += 1;
+        let score = hand.score();
+        if chosen == question.answer() {
+            self.questions_right += 1;
+            ui.add_score_player(score);
+            true
+        } else {
+            self.award_cpu(ui, score);
+            false
+        }
+    }
+
+    fn play_cards<'h>(&mut self, ui: &mut UI, hand: &Hand<'h>, mut combos: Vec<Combo<'h>>) -> bool {
+        let all = combos.clone();
         let mut player_score = 0;
-        while let Some(guess) = ui.get_guess(&hand) {
-            if let Some(index) = combos.iter().position(|x| x == &guess.cards[..]) {
+        let mut claimed: Vec<Combo<'h>> = vec![];
+        let mut log: Vec<Claim<'h>> = vec![];
+        let mut hints = 0;
+        let mut revealed: Vec<Card> = vec![];
+        // Taking back a bad guess doesn't make the hand perfect again, even if the penalty goes back.
+        let mut penalised = false;
+        loop {
+            let guess = match ui.get_action(hand) {
+                Action::Guess(guess) => guess,
+                Action::Hint => {
+                    match next_hint(&combos, hints, &mut revealed) {
+                        Some(hint) => {
+                            let cost = self.hint_cost(hints);
+                            ui.display_hint(&hint, cost);
+                            self.award_cpu(ui, cost);
+                            hints += 1;
+                        },
+                        // Nothing more to give away, so it's free.
+                        None => ui.display_hint(&Hint::Count(combos.len()), 0),
+                    }
+                    continue;
+                },
+                Action::Undo => {
+                    match log.pop() {
+                        Some(claim) => {
+                            combos = claim.combos_before;
+                            claimed.truncate(claim.claimed_before);
+                            // A wrong score stays penalised: the reply already gave away the
+                            // right one.
+                            player_score -= claim.player_points;
+                            if claim.invalid {
+                                self.award_cpu(ui, -SCORE_BAD_GUESS_INVALID_COMBO);
+                            }
+                            ui.display_undo(Some(&claim.guess));
+                        },
+                        None => ui.display_undo(None),
+                    }
+                    continue;
+                },
+                Action::Done => break,
+            };
+
+            let mut claim = Claim {
+                guess: guess.clone(),
+                combos_before: combos.clone(),
+                claimed_before: claimed.len(),
+                player_points: 0,
+                invalid: false,
+            };
+            if let Some(index) = find_guessed_combo(&mut combos, &guess.cards, guess.score) {
                 {
                     let combo = &combos[index];
                     if combo.score == guess.score {
                         ui.display_correct_guess(combo);
-                        player_score += i32::from(combo.score);
+                        claim.player_points = i32::from(combo.score);
                     } else {
                         ui.display_bad_guess_wrong_score(combo);
-                        ui.add_score_cpu(SCORE_BAD_GUESS_WRONG_SCORE);
+                        self.award_cpu(ui, SCORE_BAD_GUESS_WRONG_SCORE);
+                        penalised = true;
                     }
                 }
-                combos.remove(index);
+                claimed.push(combos.remove(index));
+            } else if let Some(category) = self.outside_drill(hand, &guess.cards) {
+                // It's real, just not what's being drilled, so there's no penalty.
+                ui.display_outside_drill(category, self.settings.drill.unwrap());
             } else {
-                ui.display_bad_guess_invalid_combo();
-                ui.add_score_cpu(SCORE_BAD_GUESS_INVALID_COMBO);
+                ui.display_bad_guess_invalid_combo(&explain::explain_rejection(hand, &guess.cards, &claimed));
+                self.award_cpu(ui, SCORE_BAD_GUESS_INVALID_COMBO);
+                claim.invalid = true;
+                penalised = true;
             }
+            player_score += claim.player_points;
+            log.push(claim);
         }
 
+        self.stats.record(&all, &combos);
+        let mut perfect = combos.is_empty() && hints == 0 && !penalised;
+
+        // The player pegs what they say they have, but no more than what they counted.
+        if let Some(declared) = ui.get_declared_total(hand) {
+            if declared != player_score {
+                ui.display_declared_total(declared, player_score);
+                player_score = player_score.min(declared.max(0));
+                perfect = false;
+            }
+        }
+        if self.settings.mode == Mode::Lowball {
+            // Every point in the hand is pegged against the player, however much of it they
+            // counted, so there's nothing to gain by missing points on purpose.
+            if combos.is_empty() {
+                ui.display_win_message(points(&all));
+            } else {
+                ui.display_missed_combos(hand, &self.present_missed(combos));
+            }
+            ui.add_score_player(points(&all));
+            return perfect;
+        }
         if combos.is_empty() {
             ui.display_win_message(player_score);
+        }
+        ui.add_score_player(player_score);
+        self.call_muggins(ui, hand, combos);
+        perfect
+    }
+
+    /// The player pegs what they claimed in each category, as long as it's not more than is there,
+    /// and the computer takes whatever a category came up short as muggins, if it notices.
+    fn play_categories(&mut self, ui: &mut UI, hand: &Hand<'_>, combos: &[Combo<'_>]) -> bool {
+        let claims = ui.get_category_claims(hand);
+        let lowball = self.settings.mode == Mode::Lowball;
+
+        let mut rng = rand::thread_rng();
+        let mut perfect = true;
+        let mut player_score = 0;
+        let mut muggins = 0;
+        let mut missed: Vec<Combo<'_>> = vec![];
+        let mut unnoticed: Vec<Combo<'_>> = vec![];
+        for category in &Category::ALL {
+            let claimed = claims.iter()
+                .filter(|claim| claim.category == *category)
+                .fold(0, |total, claim| total + claim.score);
+            let actual = combo::category_total(combos, *category);
+            if claimed == 0 && actual == 0 {
+                continue;
+            }
+
+            ui.display_category_result(*category, claimed, actual);
+            if claimed == actual {
+                player_score += i32::from(actual);
+            } else if claimed > actual {
+                self.award_cpu(ui, SCORE_BAD_GUESS_WRONG_SCORE);
+                perfect = false;
+            } else {
+                // What was claimed of it is right, and the opponent takes the difference as muggins.
+                player_score += i32::from(claimed);
+                self.stats.record_missed(*category, (actual - claimed) as u32);
+                let short = combo::shortfall(combos, *category, claimed);
+                if lowball || rng.gen_bool(self.settings.muggins_skill) {
+                    muggins += i32::from(actual - claimed);
+                    missed.extend(short);
+                } else {
+                    unnoticed.extend(short);
+                }
+                perfect = false;
+            }
+        }
+
+        self.stats.record(combos, &[]);
+        if missed.is_empty() && unnoticed.is_empty() {
+            let pegged = if lowball { points(combos) } else { player_score };
+            ui.display_win_message(pegged);
+        }
+        if !missed.is_empty() {
+            ui.display_missed_combos(hand, &self.present_missed(missed));
+            ui.display_lose_message(muggins);
+        }
+        if !unnoticed.is_empty() {
+            ui.display_unnoticed(hand, &self.present_missed(unnoticed));
+        }
+        if lowball {
+            // The whole hand is pegged against the player, claimed or missed.
+            ui.add_score_player(points(combos));
+        } else {
             ui.add_score_player(player_score);
+            if muggins > 0 {
+                ui.add_score_cpu(muggins);
+            }
+        }
+        perfect
+    }
+
+    /// Each kind of combo is checked separately: runs of three apart from runs of four, and so on.
+    /// As when naming the cards, the player pegs what they got right, up to the last total they
+    /// said, and the computer calls muggins on what's missing. Wrong running totals along the way
+    /// don't cost anything, but the count isn't perfect with them.
+    fn play_spoken(&mut self, ui: &mut UI, hand: &Hand<'_>, combos: &[Combo<'_>]) -> bool {
+        let count = ui.get_spoken_count(hand);
+
+        let mut perfect = true;
+        let mut player_score = 0;
+        let mut missed: Vec<Combo<'_>> = vec![];
+        for result in spoken::check(&count, combos) {
+            ui.display_component_result(&result);
+            let points = result.component.points();
+            if result.claimed == result.actual {
+                player_score += points * result.actual as i32;
+            } else if result.claimed > result.actual {
+                self.award_cpu(ui, SCORE_BAD_GUESS_WRONG_SCORE);
+                perfect = false;
+            } else {
+                // What was claimed of it is right, as far as it goes.
+                player_score += points * result.claimed as i32;
+                missed.extend(spoken::shortfall(&result, combos));
+                perfect = false;
+            }
+        }
+        for &(said, actual) in &count.totals {
+            if said != actual {
+                ui.display_wrong_total(said, actual);
+                perfect = false;
+            }
+        }
+
+        self.stats.record(combos, &missed);
+        if self.settings.mode == Mode::Lowball {
+            // The whole hand is pegged against the player, claimed or missed.
+            if !missed.is_empty() {
+                ui.display_missed_combos(hand, &self.present_missed(missed));
+            }
+            ui.add_score_player(points(combos));
+            return perfect;
+        }
+
+        // The last total said is what the player pegs, but no more than what they counted.
+        if let Some(&(declared, _)) = count.totals.last() {
+            if declared != player_score {
+                ui.display_declared_total(declared, player_score);
+                player_score = player_score.min(declared.max(0));
+                perfect = false;
+            }
+        }
+        if missed.is_empty() {
+            ui.display_win_message(player_score);
+        }
+        ui.add_score_player(player_score);
+        self.call_muggins(ui, hand, missed);
+        perfect
+    }
+
+    /// The computer calls muggins on each missed combo it notices, and the rest are shown
+    /// afterwards.
+    fn call_muggins<'h>(&self, ui: &mut UI, hand: &Hand<'h>, missed: Vec<Combo<'h>>) {
+        let mut unnoticed = vec![];
+        let mut rng = rand::thread_rng();
+        for combo in self.present_missed(missed) {
+            if rng.gen_bool(self.settings.muggins_skill) {
+                ui.display_muggins(hand, &combo);
+                self.award_cpu(ui, i32::from(combo.score));
+            } else {
+                unnoticed.push(combo);
+            }
+        }
+        if !unnoticed.is_empty() {
+            ui.display_unnoticed(hand, &unnoticed);
+        }
+    }
+
+    /// Points for the computer: muggins, and penalties for bad guesses and hints. In lowball,
+    /// they're pegged against the player instead.
+    fn award_cpu(&self, ui: &mut UI, points: i32) {
+        if self.settings.mode == Mode::Lowball {
+            ui.add_score_player(points);
         } else {
-            ui.display_missed_combos(&combos);
-            let score = combos.iter().fold(0i32, |score, combo| score + i32::from(combo.score));
-            ui.display_lose_message(score);
-            ui.add_score_cpu(score);
+            ui.add_score_cpu(points);
         }
     }
+
+    /// If a drill is on, and the cards make a combo of some other category, which category.
+    fn outside_drill(&self, hand: &Hand<'_>, cards: &[Card]) -> Option<Category> {
+        let drill = self.settings.drill?;
+        hand.find_all_combos().iter()
+            .find(|combo| combo.category() != drill && *combo == cards)
+            .map(Combo::category)
+    }
+
+    fn hint_cost(&self, hints_so_far: usize) -> i32 {
+        let costs = &self.settings.hint_costs;
+        costs.get(hints_so_far).or_else(|| costs.last()).cloned().unwrap_or(0)
+    }
+
+    fn present_missed<'h>(&self, combos: Vec<Combo<'h>>) -> Vec<Combo<'h>> {
+        if self.settings.compound_runs {
+            combo::combine_compounds(combos)
+        } else {
+            combos
+        }
+    }
+}
+
+/// What the combos are worth altogether.
+fn points(combos: &[Combo<'_>]) -> i32 {
+    combos.iter().fold(0, |score, combo| score + i32::from(combo.score))
+}
+
+/// Deals several rounds, and keeps the one where the player's hand has the most points in the
+/// category being drilled. Ties go to whichever uses more cards, so that fifteens of three or four
+/// cards come up more often than a ten and a five.
+fn deal_drill<R: Rng>(deck: &Deck, rules: &Rules, category: Category, rng: &mut R) -> Round {
+    deal_best(deck, rules, DRILL_DEALS, rng, |hand| {
+        let combos: Vec<Combo<'_>> = hand.find_all_combos().into_iter()
+            .filter(|combo| combo.category() == category)
+            .collect();
+        let cards = combos.iter().fold(0, |cards, combo| cards + combo.cards.len());
+        (combo::category_total(&combos, category), cards)
+    })
+}
+
+/// Deals the given number of rounds, starting with the deck as it is and then shuffling it, and
+/// keeps the one where the player's hand is worth the most.
+fn deal_best<R, K, F>(deck: &Deck, rules: &Rules, deals: usize, rng: &mut R, value: F) -> Round
+        where R: Rng, K: PartialOrd, F: Fn(&Hand<'_>) -> K {
+    let mut deck = deck.clone();
+    let mut best = deck.deal_round(rules);
+    let mut best_value = value(&best.hand(0));
+    for _ in 1 .. deals {
+        deck.shuffle_with(rng);
+        let round = deck.deal_round(rules);
+        let round_value = value(&round.hand(0));
+        if round_value > best_value {
+            best = round;
+            best_value = round_value;
+        }
+    }
+    best
+}
+
+/// Gives away a little more about the combos that are left each time: first how many there are,
+/// then what categories they are, and then one card at a time. Once there's nothing left that
+/// hasn't been given away, there's no hint.
+fn next_hint(combos: &[Combo<'_>], hints_so_far: usize, revealed: &mut Vec<Card>) -> Option<Hint> {
+    if combos.is_empty() {
+        return None;
+    }
+    match hints_so_far {
+        0 => Some(Hint::Count(combos.len())),
+        1 => {
+            let counts = Category::ALL.iter()
+                .map(|&category| (category, combos.iter().filter(|x| x.category() == category).count()))
+                .filter(|&(_, n)| n > 0)
+                .collect();
+            Some(Hint::Categories(counts))
+        },
+        _ => {
+            for combo in combos {
+                if let Some(card) = combo.cards.iter().find(|card| !revealed.contains(card)) {
+                    revealed.push(**card);
+                    return Some(Hint::Card(**card, combo.kind));
+                }
+            }
+            None
+        },
+    }
+}
+
+/// Finds the combo matching the guessed cards. Three or four of a kind may be claimed either as a
+/// whole group or one pair at a time, regardless of how the combos are presented, and runs may be
+/// claimed together with their pairs as a double run or the like. The combos are regrouped or
+/// split as needed so the guess can be matched.
+fn find_guessed_combo(combos: &mut Vec<Combo<'_>>, cards: &[Card], score: i8) -> Option<usize> {
+    // The same cards can make more than one combo (3 3 4 5 is both a fifteen and a double run),
+    // so prefer whichever one the score fits.
+    if let Some(index) = combos.iter().position(|x| x == cards && x.score == score) {
+        return Some(index);
+    }
+    let compound = combo::find_compounds(combos).into_iter()
+        .find(|compound| &compound.combo == cards && compound.combo.score == score);
+    if let Some(compound) = compound {
+        return Some(combine_compound(combos, compound));
+    }
+
+    if let Some(index) = combos.iter().position(|x| x == cards) {
+        return Some(index);
+    }
+    let compound = combo::find_compounds(combos).into_iter()
+        .find(|compound| &compound.combo == cards);
+    if let Some(compound) = compound {
+        return Some(combine_compound(combos, compound));
+    }
+
+    if cards.len() < 2 || cards.iter().any(|card| card.number != cards[0].number) {
+        return None;
+    }
+
+    if cards.len() == 2 {
+        // One pair out of a bigger group.
+        let index = combos.iter().position(|x| {
+            x.kind.of_a_kind_count() > 2 && cards.iter().all(|card| x.cards.contains(&card))
+        })?;
+        let group = combos.remove(index);
+        for (i, pair) in group.pairs().into_iter().enumerate() {
+            combos.insert(index + i, pair);
+        }
+    } else {
+        // A whole group, while its pairs are listed separately. All of them must still be there.
+        let indices: Vec<usize> = combos.iter()
+            .enumerate()
+            .filter(|(_, x)| {
+                x.kind == ComboKind::Pair && x.cards.iter().all(|card| cards.contains(card))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if indices.len() != util::binomial_coefficient(cards.len() as i8, 2) as usize {
+            return None;
+        }
+
+        let mut group_cards = vec![];
+        for &i in &indices {
+            for card in &combos[i].cards {
+                if !group_cards.contains(card) {
+                    group_cards.push(*card);
+                }
+            }
+        }
+        for &i in indices.iter().rev() {
+            combos.remove(i);
+        }
+        combos.insert(indices[0], Combo::new(group_cards, ComboKind::of_a_kind(cards.len())));
+    }
+
+    combos.iter().position(|x| x == cards)
+}
+
+/// Replaces the components of the compound combo with the compound itself, returning its index.
+fn combine_compound<'a>(combos: &mut Vec<Combo<'a>>, compound: Compound<'a>) -> usize {
+    for &i in compound.components.iter().rev() {
+        combos.remove(i);
+    }
+    let index = compound.components[0];
+    combos.insert(index, compound.combo);
+    index
+}
+
+#[test]
+fn test_next_hint() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h kh 5d 3s 4s");
+    let hand = Hand::new(&cards);
+    let combos = hand.find_all_combos();
+    let mut revealed = vec![];
+
+    match next_hint(&combos, 0, &mut revealed) {
+        Some(Hint::Count(n)) => assert_eq!(n, 5),
+        _ => panic!("expected a count"),
+    }
+    match next_hint(&combos, 1, &mut revealed) {
+        Some(Hint::Categories(counts)) => assert_eq!(counts,
+            vec![(Category::Fifteens, 2), (Category::Pairs, 1), (Category::Runs, 2)]),
+        _ => panic!("expected categories"),
+    }
+    let mut cards = vec![];
+    for hints in 2 .. 7 {
+        match next_hint(&combos, hints, &mut revealed) {
+            Some(Hint::Card(card, _)) => cards.push(card.to_string()),
+            _ => panic!("expected a card"),
+        }
+    }
+    // The two fifteens give away all three cards between them, and then the runs the rest.
+    assert_eq!(cards, vec!["5H", "KH", "5D", "3S", "4S"]);
+    assert!(next_hint(&combos, 7, &mut revealed).is_none());
+    assert!(next_hint(&[], 0, &mut revealed).is_none());
+
+    // Only the hints that give something away cost anything: 1 + 1 + 2 for each of the five cards.
+    let ui = RefCell::new(ScriptedUI::new((0 .. 9).map(|_| Action::Hint).collect()));
+    Game::new(&ui, Settings::default()).play_hand(&hand);
+    assert_eq!(ui.borrow().cpu_score, 12 + hand.score());
+}
+
+#[test]
+fn test_find_guessed_combo() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("4h 4d 4s 2c 9c");
+    let hand = Hand::new(&cards);
+
+    // Grouped, claimed pair by pair.
+    let mut combos = hand.find_all_combos();
+    let count = combos.len();
+    let index = find_guessed_combo(&mut combos, &parse_cards("4d 4s"), 2).unwrap();
+    assert_eq!(combos[index].kind, ComboKind::Pair);
+    assert_eq!(combos.len(), count + 2);
+    combos.remove(index);
+    assert!(find_guessed_combo(&mut combos, &parse_cards("4s 4d"), 2).is_none());
+    assert!(find_guessed_combo(&mut combos, &parse_cards("4s 4d 4h"), 6).is_none());
+    assert!(find_guessed_combo(&mut combos, &parse_cards("4s 4h"), 2).is_some());
+
+    // Individual, claimed as a group.
+    let mut combos = combo::split_pairs(hand.find_all_combos());
+    let count = combos.len();
+    let index = find_guessed_combo(&mut combos, &parse_cards("4s 4d 4h"), 6).unwrap();
+    assert_eq!(combos[index].kind, ComboKind::PairRoyal);
+    assert_eq!(combos[index].score, 6);
+    assert_eq!(combos.len(), count - 2);
+
+    assert!(find_guessed_combo(&mut combos, &parse_cards("2c 9c"), 2).is_none());
+
+    // A double run that's also a fifteen.
+    let cards = parse_cards("3h 3d 4s 5c kc");
+    let hand = Hand::new(&cards);
+    let mut combos = hand.find_all_combos();
+    let count = combos.len();
+    let index = find_guessed_combo(&mut combos, &parse_cards("3h 3d 4s 5c"), 2).unwrap();
+    assert_eq!(combos[index].kind, ComboKind::Fifteen);
+    assert_eq!(combos.len(), count);
+    let index = find_guessed_combo(&mut combos, &parse_cards("3h 3d 4s 5c"), 8).unwrap();
+    assert_eq!(combos[index].kind, ComboKind::DoubleRun(3));
+    assert_eq!(combos.len(), count - 2);
+}
+
+/// Plays back a list of actions, and keeps score.
+#[cfg(test)]
+struct ScriptedUI {
+    actions: Vec<Action>,
+    player_score: i32,
+    cpu_score: i32,
+    undone: Vec<Option<Guess>>,
+    cross_lines: usize,
+    choices: usize,
+    categories: Vec<Vec<CategoryClaim>>,
+    spoken: Vec<SpokenCount>,
+    declared: Option<i32>,
+    unnoticed: usize,
+    outside_drill: Vec<Category>,
+}
+
+#[cfg(test)]
+impl ScriptedUI {
+    fn new(actions: Vec<Action>) -> ScriptedUI {
+        ScriptedUI {
+            actions,
+            player_score: 0,
+            cpu_score: 0,
+            undone: vec![],
+            cross_lines: 0,
+            choices: 0,
+            categories: vec![],
+            spoken: vec![],
+            declared: None,
+            unnoticed: 0,
+            outside_drill: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+impl UserInterface for ScriptedUI {
+    fn display_hand(&mut self, _hand: &Hand<'_>) {}
+    fn display_missed_combos(&mut self, _hand: &Hand<'_>, _combos: &[Combo<'_>]) {}
+    fn display_win_message(&mut self, _score: i32) {}
+    fn display_lose_message(&mut self, _score: i32) {}
+    fn display_bad_guess_wrong_score(&mut self, _actual_combo: &Combo<'_>) {}
+    fn display_bad_guess_invalid_combo(&mut self, _reasons: &[String]) {}
+    fn display_correct_guess(&mut self, _combo: &Combo<'_>) {}
+    fn display_outside_drill(&mut self, category: Category, _drill: Category) {
+        self.outside_drill.push(category);
+    }
+    fn add_score_player(&mut self, score: i32) {
+        self.player_score += score;
+    }
+    fn add_score_cpu(&mut self, score: i32) {
+        self.cpu_score += score;
+    }
+    fn display_category_result(&mut self, _category: Category, _claimed: i8, _actual: i8) {}
+    fn display_hint(&mut self, _hint: &Hint, _cost: i32) {}
+    fn display_undo(&mut self, guess: Option<&Guess>) {
+        self.undone.push(guess.cloned());
+    }
+    fn display_pone_bonus(&mut self, _points: i32) {}
+    fn display_cpu_hand(&mut self, _hand: &Hand<'_>, _score: i32) {}
+    fn display_cross(&mut self, _grid: &CrossGrid, _line: Option<Line>) {
+        self.cross_lines += 1;
+    }
+    fn display_cpu_announcement(&mut self, _hand: &Hand<'_>, _combos: &[Combo<'_>]) {}
+    fn get_muggins_calls(&mut self, _hand: &Hand<'_>) -> Vec<Vec<Card>> {
+        self.actions.drain(..)
+            .filter_map(|action| match action {
+                Action::Guess(guess) => Some(guess.cards),
+                _ => None,
+            })
+            .collect()
+    }
+    fn display_verdict(&mut self, _hand: &Hand<'_>, _verdict: &Verdict<'_>) {}
+    fn get_choice(&mut self, _hand: &Hand<'_>, question: &Question<'_>) -> usize {
+        // Guess right, then wrong, then right again, and so on.
+        self.choices += 1;
+        if self.choices % 2 == 1 {
+            question.answer()
+        } else {
+            (question.answer() + 1) % choice::CHOICES
+        }
+    }
+    fn display_choice_result(&mut self, _hand: &Hand<'_>, _question: &Question<'_>, _chosen: usize) {}
+    fn display_lesson(&mut self, _unit: &Unit) {}
+    fn display_lesson_result(&mut self, _unit: &Unit, _perfect: usize) {}
+    fn get_starter_answer(&mut self, held: &[Card], question: &StarterQuestion) -> Vec<i8> {
+        // Right, then wrong, like the multiple-choice questions.
+        self.choices += 1;
+        if self.choices % 2 == 1 {
+            let mut answer = question.answer(&starters::by_number(&starters::starter_scores(held, &[], false)));
+            if *question == StarterQuestion::Best {
+                answer.truncate(1);
+            }
+            answer
+        } else {
+            vec![]
+        }
+    }
+    fn display_starter_result(&mut self, _held: &[Card], _question: &StarterQuestion,
+        _numbers: &[starters::NumberScore], _right: bool) {}
+    fn get_action(&mut self, _hand: &Hand<'_>) -> Action {
+        if self.actions.is_empty() {
+            Action::Done
+        } else {
+            self.actions.remove(0)
+        }
+    }
+    fn get_category_claims(&mut self, _hand: &Hand<'_>) -> Vec<CategoryClaim> {
+        if self.categories.is_empty() {
+            vec![]
+        } else {
+            self.categories.remove(0)
+        }
+    }
+    fn get_spoken_count(&mut self, _hand: &Hand<'_>) -> SpokenCount {
+        if self.spoken.is_empty() {
+            SpokenCount::default()
+        } else {
+            self.spoken.remove(0)
+        }
+    }
+    fn display_component_result(&mut self, _result: &ComponentResult) {}
+    fn get_declared_total(&mut self, _hand: &Hand<'_>) -> Option<i32> {
+        self.declared.take()
+    }
+    fn display_declared_total(&mut self, _declared: i32, _counted: i32) {}
+    fn display_muggins(&mut self, _hand: &Hand<'_>, _combo: &Combo<'_>) {}
+    fn display_unnoticed(&mut self, _hand: &Hand<'_>, combos: &[Combo<'_>]) {
+        self.unnoticed += combos.len();
+    }
+    fn display_wrong_total(&mut self, _said: i32, _actual: i32) {}
+}
+
+#[test]
+fn test_undo() {
+    use crate::card::parse_cards;
+
+    let guess = |s: &str, score: i8| Action::Guess(Guess { cards: parse_cards(s), score });
+
+    let cards = parse_cards("5h kh 2c 9s 4d");
+    let hand = Hand::with_starter(&cards);
+    let ui = RefCell::new(ScriptedUI::new(vec![
+        guess("5h kh", 2),
+        guess("5h kh 2c", 2),  // a typo, costing nothing once it's taken back
+        Action::Undo,
+        guess("2c 9s 4d", 3),  // wrong score, costing 1
+        Action::Undo,
+        guess("2c 9s 4d", 2),
+    ]));
+    // Everything got counted in the end, but not perfectly.
+    assert!(!Game::new(&ui, Settings::default()).play_hand(&hand));
+
+    let ui = ui.into_inner();
+    assert_eq!(ui.undone.len(), 2);
+    assert_eq!(ui.undone[0].as_ref().unwrap().cards, parse_cards("5h kh 2c"));
+    assert_eq!(ui.cpu_score, SCORE_BAD_GUESS_WRONG_SCORE);
+    assert_eq!(ui.player_score, 4);
+
+    // Counting it all unaided is perfect, but not after a hint.
+    let ui = RefCell::new(ScriptedUI::new(vec![guess("5h kh", 2), guess("2c 9s 4d", 2)]));
+    assert!(Game::new(&ui, Settings::default()).play_hand(&hand));
+    let ui = RefCell::new(ScriptedUI::new(vec![Action::Hint, guess("5h kh", 2), guess("2c 9s 4d", 2)]));
+    assert!(!Game::new(&ui, Settings::default()).play_hand(&hand));
+
+    // Nothing left to undo after undoing everything.
+    let ui = RefCell::new(ScriptedUI::new(vec![guess("5h kh", 2), Action::Undo, Action::Undo]));
+    Game::new(&ui, Settings::default()).play_hand(&hand);
+    let ui = ui.into_inner();
+    assert!(ui.undone[0].is_some());
+    assert!(ui.undone[1].is_none());
+    assert_eq!(ui.player_score, 0);
+    assert_eq!(ui.cpu_score, 4);
+}
+
+#[test]
+fn test_play_rounds() {
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings {
+        rules: Rules::FIVE_CARD,
+        .. Settings::default()
+    };
+    let mut game = Game::new(&ui, settings);
+    let deck = Deck::new();
+
+    // Claiming nothing gives every point to the computer, apart from the bonus for not dealing.
+    game.play(&deck);
+    let round = deck.deal_round(&Rules::FIVE_CARD);
+    let hand_score = round.hand(0).score();
+    assert_eq!(ui.borrow().player_score, 3);
+    assert_eq!(ui.borrow().cpu_score, hand_score);
+
+    // The second time around, the player deals and counts the crib too.
+    game.play(&deck);
+    assert_eq!(ui.borrow().player_score, 3);
+    assert_eq!(ui.borrow().cpu_score, 2 * hand_score + round.crib().score());
+}
+
+#[test]
+fn test_dealer_rotation() {
+    // Around a table of three, the player only gets the crib every third hand.
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { rules: Rules::THREE_PLAYER, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+    let deck = Deck::new();
+    let round = deck.deal_round(&Rules::THREE_PLAYER);
+    let hand_score = round.hand(0).score();
+    let crib_score = round.crib().score();
+    let mut expected = 0;
+    for hand in 0 .. 6 {
+        game.play(&deck);
+        expected += hand_score + if hand % 3 == 2 { crib_score } else { 0 };
+        assert_eq!(ui.borrow().cpu_score, expected, "hand {}", hand);
+    }
+}
+
+#[test]
+fn test_cpu_discard() {
+    // In lowball, the computer keeps the cards its strategy rates lowest, rather than the last
+    // ones dealt, and counts them.
+    let deck = Deck::new();
+    let rules = Rules::STANDARD;
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { mode: Mode::Lowball, cpu_discard: Some("guaranteed"), .. Settings::default() };
+    Game::new(&ui, settings).play(&deck);
+
+    let mut round = deck.deal_round(&rules);
+    let dealt = round.dealt(1, &rules);
+    let worst = discard::rank(&mut discard::GuaranteedPoints, &dealt, rules.discard, true).pop().unwrap();
+    assert_ne!(worst.kept, round.hand(1).held_cards());
+    round.rethrow(1, &rules, &worst.kept, &worst.thrown);
+    assert_eq!(ui.borrow().cpu_score, round.hand(1).score());
+}
+
+#[test]
+fn test_variant_modes() {
+    use crate::card::parse_cards;
+
+    let scripted = || RefCell::new(ScriptedUI::new(vec![]));
+    let deck = Deck::new();
+    let round = deck.deal_round(&Rules::STANDARD);
+
+    // In lowball, the points the player doesn't claim are pegged against them, and the computer
+    // counts its own hand.
+    let ui = scripted();
+    let settings = Settings { mode: Mode::Lowball, .. Settings::default() };
+    Game::new(&ui, settings).play(&deck);
+    assert_eq!(ui.borrow().player_score, round.hand(0).score());
+    assert_eq!(ui.borrow().cpu_score, round.hand(1).score());
+
+    // Claiming only part of the hand doesn't save the player anything, whichever way they count.
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let lowball = |guess_style| Settings { mode: Mode::Lowball, guess_style, muggins_skill: 0., .. Settings::default() };
+
+    let ui = RefCell::new(ScriptedUI::new(vec![Action::Guess(Guess { cards: cards[.. 2].to_vec(), score: 2 })]));
+    ui.borrow_mut().declared = Some(0);
+    assert!(!Game::new(&ui, lowball(GuessStyle::Cards)).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 12);
+
+    let ui = scripted();
+    ui.borrow_mut().categories = vec![vec![CategoryClaim { category: Category::Pairs, score: 2 }]];
+    assert!(!Game::new(&ui, lowball(GuessStyle::Categories)).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 12);
+
+    let ui = scripted();
+    ui.borrow_mut().spoken = vec![spoken::parse("a pair is two").unwrap()];
+    assert!(!Game::new(&ui, lowball(GuessStyle::Spoken)).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 12);
+    assert_eq!(ui.borrow().cpu_score, 0);
+
+    let ui = scripted();
+    let settings = Settings { mode: Mode::CrossCribbage, .. Settings::default() };
+    Game::new(&ui, settings).play(&deck);
+    let grid = CrossGrid::deal(&deck);
+    let (rows, columns) = grid.totals();
+    assert_eq!(ui.borrow().cross_lines, 11);
+    assert_eq!(ui.borrow().cpu_score, rows + columns + grid.crib().score());
+}
+
+#[test]
+fn test_play_catch() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let settings = Settings { mode: Mode::CatchTheComputer, cpu_error_rate: 0., .. Settings::default() };
+
+    // An honest count, accepted.
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    Game::new(&ui, settings).play_catch(&hand);
+    assert_eq!(ui.borrow().cpu_score, hand.score());
+    assert_eq!(ui.borrow().player_score, 0);
+
+    // Calling muggins on an honest count costs the player.
+    let settings = Settings { mode: Mode::CatchTheComputer, cpu_error_rate: 0., .. Settings::default() };
+    let ui = RefCell::new(ScriptedUI::new(vec![Action::Guess(Guess { cards: parse_cards("5h 5d"), score: 2 })]));
+    Game::new(&ui, settings).play_catch(&hand);
+    assert_eq!(ui.borrow().cpu_score, hand.score() + SCORE_BAD_MUGGINS_CALL);
+}
+
+#[test]
+fn test_multiple_choice() {
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { guess_style: GuessStyle::MultipleChoice, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+    let deck = Deck::new();
+    let score = deck.deal_round(&Rules::STANDARD).hand(0).score();
+
+    game.play(&deck);
+    assert_eq!(game.accuracy(), (1, 1));
+    assert_eq!(ui.borrow().player_score, score);
+
+    // The second hand is the player's deal, so they answer for the crib as well.
+    game.play(&deck);
+    assert_eq!(game.accuracy(), (2, 3));
+}
+
+#[test]
+fn test_drill() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::card::parse_cards;
+
+    // The unshuffled deck deals the player two aces and two twos, with a four for the starter:
+    // no fifteens at all. Some other deal has them.
+    let deck = Deck::new();
+    assert_eq!(combo::category_total(&deck.deal_round(&Rules::STANDARD).hand(0).find_all_combos(),
+        Category::Fifteens), 0);
+    let round = deal_drill(&deck, &Rules::STANDARD, Category::Fifteens, &mut StdRng::seed_from_u64(3));
+    assert!(combo::category_total(&round.hand(0).find_all_combos(), Category::Fifteens) > 0);
+
+    let cards = parse_cards("5h kh 5d 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let ui = RefCell::new(ScriptedUI::new(vec![
+        Action::Guess(Guess { cards: parse_cards("5h 5d"), score: 2 }),
+        Action::Guess(Guess { cards: parse_cards("5h kh"), score: 2 }),
+        Action::Guess(Guess { cards: parse_cards("5d kh"), score: 2 }),
+    ]));
+    let settings = Settings { drill: Some(Category::Fifteens), .. Settings::default() };
+    Game::new(&ui, settings).play_hand(&hand);
+
+    // The pair doesn't count, but doesn't cost anything either; the runs are ignored.
+    assert_eq!(ui.borrow().player_score, 4);
+    assert_eq!(ui.borrow().cpu_score, 0);
+    assert_eq!(ui.borrow().outside_drill, vec![Category::Pairs]);
+}
+
+#[test]
+fn test_adaptive() {
+    use crate::stats::CategoryStats;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h kh 5d 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    // Claim the runs and the pair, but none of the fifteens.
+    let ui = RefCell::new(ScriptedUI::new(vec![
+        Action::Guess(Guess { cards: parse_cards("5h 5d"), score: 2 }),
+        Action::Guess(Guess { cards: parse_cards("3s 4s 5h"), score: 3 }),
+        Action::Guess(Guess { cards: parse_cards("3s 4s 5d"), score: 3 }),
+    ]));
+    let settings = Settings { adaptive: true, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+    game.play_hand(&hand);
+    assert_eq!(game.stats().weakest(), Some(Category::Fifteens));
+
+    // Claiming one pair out of a pair royal leaves the other two to be missed, out of the same six
+    // points.
+    let cards = parse_cards("4h 4d 4s kc qd");
+    let ui = RefCell::new(ScriptedUI::new(vec![Action::Guess(Guess { cards: parse_cards("4h 4d"), score: 2 })]));
+    let mut game = Game::new(&ui, Settings::default());
+    game.play_hand(&Hand::with_starter(&cards));
+    assert_eq!(game.stats().categories[&Category::Pairs], CategoryStats { seen: 6, missed: 4 });
+    assert!(game.stats().accuracy() < 0.5);
+
+    // A player who counts everything but fifteens gets dealt far more of them than random deals
+    // have.
+    let mut stats = Stats::default();
+    for category in &Category::ALL {
+        let missed = if *category == Category::Fifteens { 50 } else { 0 };
+        stats.categories.insert(*category, CategoryStats { seen: 100, missed });
+    }
+    let fifteens = |round: &Round| combo::category_total(&round.hand(0).find_all_combos(), Category::Fifteens);
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut deck = Deck::new();
+    let (mut random, mut adaptive) = (0, 0);
+    for _ in 0 .. 20 {
+        deck.shuffle_with(&mut rng);
+        random += i32::from(fifteens(&deck.deal_round(&Rules::STANDARD)));
+        let round = deal_best(&deck, &Rules::STANDARD, stats.adaptive_deals(), &mut rng, |hand| stats.weakness(hand));
+        adaptive += i32::from(fifteens(&round));
+    }
+    assert!(adaptive > 2 * random, "{} fifteen points dealt, against {} at random", adaptive, random);
+}
+
+#[test]
+fn test_play_lesson() {
+    use crate::combo::ComboKind;
+    use crate::lessons;
+
+    // Deal the same hands ahead of time, and claim nobs in all but the last.
+    let unit = lessons::find_unit("nobs").unwrap();
+    let mut actions = vec![];
+    let mut generator = Generator::new(9, Rules::STANDARD);
+    for _ in 0 .. unit.exercises - 1 {
+        let cards = generator.hand(unit.constraints).unwrap();
+        let hand = Hand::with_starter(&cards);
+        let nobs = hand.find_all_combos().into_iter().find(|x| x.kind == ComboKind::Nobs).unwrap();
+        actions.push(Action::Guess(Guess { cards: nobs.cards.iter().map(|card| **card).collect(), score: 1 }));
+        actions.push(Action::Done);
+    }
+
+    let ui = RefCell::new(ScriptedUI::new(actions));
+    let mut game = Game::new(&ui, Settings::default());
+    let mut generator = Generator::new(9, Rules::STANDARD);
+    assert_eq!(game.play_lesson(unit, &mut generator), unit.exercises - 1);
+    assert!(game.settings().drill.is_none());
+    assert_eq!(ui.borrow().player_score, unit.exercises as i32 - 1);
+    assert_eq!(ui.borrow().cpu_score, 1);
+}
+
+#[test]
+fn test_starter_quiz() {
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { mode: Mode::BestStarter, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+    let mut deck = Deck::new();
+    for _ in 0 .. 4 {
+        deck.shuffle();
+        game.play(&deck);
+    }
+    assert_eq!(game.accuracy(), (2, 4));
+    assert_eq!(ui.borrow().player_score, 2 * SCORE_STARTER_QUIZ);
+    assert_eq!(ui.borrow().cpu_score, 2 * SCORE_STARTER_QUIZ);
+}
+
+#[test]
+fn test_spoken() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { guess_style: GuessStyle::Spoken, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+
+    ui.borrow_mut().spoken = vec![
+        spoken::parse("fifteen two, fifteen four, and a double run is twelve").unwrap(),
+        // Right, but added up wrong along the way.
+        spoken::parse("fifteen four, a pair is eight, two runs of three is twelve").unwrap(),
+        // Two fifteens short.
+        spoken::parse("a double run is eight").unwrap(),
+    ];
+    assert!(game.play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 12);
+    assert!(!game.play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 24);
+    assert!(!game.play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 32);
+    assert_eq!(ui.borrow().cpu_score, 4);
+
+    // Saying less than was counted pegs only that much, and a computer that doesn't notice the
+    // fifteens missed lets them go.
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    ui.borrow_mut().spoken = vec![spoken::parse("a double run is six").unwrap()];
+    let settings = Settings { guess_style: GuessStyle::Spoken, muggins_skill: 0., .. Settings::default() };
+    assert!(!Game::new(&ui, settings).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 6);
+    assert_eq!(ui.borrow().cpu_score, 0);
+    assert_eq!(ui.borrow().unnoticed, 2);
+}
+
+#[test]
+fn test_categories() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let claim = |category, score| CategoryClaim { category, score };
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    let settings = Settings { guess_style: GuessStyle::Categories, .. Settings::default() };
+    let mut game = Game::new(&ui, settings);
+
+    ui.borrow_mut().categories = vec![
+        vec![claim(Category::Fifteens, 4), claim(Category::Pairs, 2), claim(Category::Runs, 6)],
+        // One run short: the rest still counts, and only the missing run goes to the computer.
+        vec![claim(Category::Fifteens, 4), claim(Category::Pairs, 2), claim(Category::Runs, 3)],
+    ];
+    assert!(game.play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 12);
+    assert_eq!(ui.borrow().cpu_score, 0);
+    assert!(!game.play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 21);
+    assert_eq!(ui.borrow().cpu_score, 3);
+    assert_eq!(game.stats.accuracy(), f64::from(24 - 3 + 1) / f64::from(24 + 2));
+
+    // A computer with no skill at muggins lets a fifteen, the pair, and a run go.
+    let ui = RefCell::new(ScriptedUI::new(vec![]));
+    ui.borrow_mut().categories = vec![vec![claim(Category::Fifteens, 2), claim(Category::Runs, 3)]];
+    let settings = Settings { guess_style: GuessStyle::Categories, muggins_skill: 0., .. Settings::default() };
+    assert!(!Game::new(&ui, settings).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 5);
+    assert_eq!(ui.borrow().cpu_score, 0);
+    assert_eq!(ui.borrow().unnoticed, 3);
+}
+
+#[test]
+fn test_muggins() {
+    use crate::card::parse_cards;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let pair = || Action::Guess(Guess { cards: vec![cards[0], cards[1]], score: 2 });
+
+    // Claiming more than was counted only gets what was counted, and a computer with no skill at
+    // muggins lets the rest go.
+    let ui = RefCell::new(ScriptedUI::new(vec![pair()]));
+    ui.borrow_mut().declared = Some(3);
+    let settings = Settings { muggins_skill: 0., .. Settings::default() };
+    assert!(!Game::new(&ui, settings).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 2);
+    assert_eq!(ui.borrow().cpu_score, 0);
+    assert_eq!(ui.borrow().unnoticed, 4);
+
+    // A perfect computer takes every one of them.
+    let ui = RefCell::new(ScriptedUI::new(vec![pair()]));
+    assert!(!Game::new(&ui, Settings::default()).play_hand(&hand));
+    assert_eq!(ui.borrow().player_score, 2);
+    assert_eq!(ui.borrow().cpu_score, 10);
+    assert_eq!(ui.borrow().unnoticed, 0);
 }
@@ -0,0 +1,140 @@
+// Generate :: deals hands that match what a drill or lesson is asking for.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// This just keeps dealing until something matches, so asking for something very rare (like a 28
+// hand) can run out of tries. Everything is dealt from a seeded generator, so the same seed always
+// gives the same hands.
+//
+
+use std::ops::RangeInclusive;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::card::Card;
+use crate::combo::{self, Category, ComboKind};
+use crate::deck::Deck;
+use crate::hand::Hand;
+use crate::rules::Rules;
+
+/// How many deals to try before giving up.
+pub const MAX_TRIES: usize = 100_000;
+
+/// Something a generated hand has to have.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Constraint {
+    /// The total, inclusive at both ends.
+    Score(RangeInclusive<i32>),
+    /// A run with one of its numbers doubled.
+    DoubleRun,
+    /// At least this many fifteens.
+    Fifteens(usize),
+    /// At least one fifteen, and every fifteen made of a number of cards in the range.
+    FifteenSizes(RangeInclusive<usize>),
+    /// At least this many points in the category.
+    Category(Category, i8),
+    Nobs,
+    /// A flush. For a crib, that means all five cards.
+    Flush,
+}
+
+impl Constraint {
+    pub fn matches(&self, hand: &Hand<'_>) -> bool {
+        let combos = hand.find_all_combos();
+        match self {
+            Constraint::Score(range) => range.contains(&hand.score()),
+            Constraint::DoubleRun => combo::find_compounds(&combos).iter()
+                .any(|compound| matches!(compound.combo.kind, ComboKind::DoubleRun(_))),
+            Constraint::Fifteens(n) => combos.iter().filter(|x| x.kind == ComboKind::Fifteen).count() >= *n,
+            Constraint::FifteenSizes(sizes) => {
+                let mut fifteens = combos.iter().filter(|x| x.kind == ComboKind::Fifteen).peekable();
+                fifteens.peek().is_some() && fifteens.all(|x| sizes.contains(&x.cards.len()))
+            },
+            Constraint::Category(category, points) => combo::category_total(&combos, *category) >= *points,
+            Constraint::Nobs => combos.iter().any(|x| x.kind == ComboKind::Nobs),
+            Constraint::Flush => combos.iter().any(|x| x.category() == Category::Flush),
+        }
+    }
+}
+
+pub struct Generator {
+    seed: u64,
+    rng: StdRng,
+    deck: Deck,
+    rules: Rules,
+}
+
+impl Generator {
+    pub fn new(seed: u64, rules: Rules) -> Generator {
+        Generator {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            deck: Deck::new(),
+            rules,
+        }
+    }
+
+    /// A generator with a random seed.
+    pub fn from_entropy(rules: Rules) -> Generator {
+        Generator::new(rand::thread_rng().gen(), rules)
+    }
+
+    /// The seed to give `new` to get the same hands again.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A hand matching all the constraints: the cards kept, followed by the starter.
+    pub fn hand(&mut self, constraints: &[Constraint]) -> Option<Vec<Card>> {
+        self.generate(false, constraints)
+    }
+
+    /// A crib matching all the constraints, followed by the starter.
+    pub fn crib(&mut self, constraints: &[Constraint]) -> Option<Vec<Card>> {
+        self.generate(true, constraints)
+    }
+
+    fn generate(&mut self, crib: bool, constraints: &[Constraint]) -> Option<Vec<Card>> {
+        for _ in 0 .. MAX_TRIES {
+            self.deck.shuffle_with(&mut self.rng);
+            let round = self.deck.deal_round(&self.rules);
+            let hand = if crib { round.crib() } else { round.hand(0) };
+            if constraints.iter().all(|constraint| constraint.matches(&hand)) {
+                return Some(hand.cards.to_vec());
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_generator() {
+    let constraints = [Constraint::Fifteens(3), Constraint::Score(8 ..= 16)];
+    let mut generator = Generator::new(42, Rules::STANDARD);
+    let hands: Vec<Vec<Card>> = (0 .. 5).map(|_| generator.hand(&constraints).unwrap()).collect();
+    for cards in &hands {
+        let hand = Hand::with_starter(cards);
+        assert_eq!(hand.held_cards().len(), 4);
+        assert!(constraints.iter().all(|constraint| constraint.matches(&hand)));
+    }
+
+    // The same seed gives the same hands.
+    let mut again = Generator::new(generator.seed(), Rules::STANDARD);
+    assert_eq!(again.hand(&constraints).unwrap(), hands[0]);
+
+    let cards = generator.crib(&[Constraint::Flush]).unwrap();
+    assert!(cards.iter().all(|card| card.suit == cards[0].suit));
+
+    let cards = generator.hand(&[Constraint::DoubleRun, Constraint::Nobs]).unwrap();
+    let hand = Hand::with_starter(&cards);
+    assert!(Constraint::DoubleRun.matches(&hand) && Constraint::Nobs.matches(&hand));
+
+    let cards = generator.hand(&[Constraint::FifteenSizes(3 ..= 5)]).unwrap();
+    let hand = Hand::with_starter(&cards);
+    assert!(hand.find_all_combos().iter().any(|x| x.kind == ComboKind::Fifteen));
+    assert!(hand.find_all_combos().iter().all(|x| x.kind != ComboKind::Fifteen || x.cards.len() >= 3));
+
+    let mut generator = Generator::new(1, Rules::FIVE_CARD);
+    assert_eq!(generator.hand(&[Constraint::Category(Category::Pairs, 2)]).unwrap().len(), 4);
+}
@@ -5,32 +5,86 @@
 
 use std::fmt::{self, Display, Formatter};
 
-use crate::card::Card;
-use crate::combo::Combo;
+use crate::card::{self, Card};
+use crate::combo::{Combo, ComboKind};
+use crate::packed::{self, CardSet};
+use crate::table;
 use crate::util;
 
 #[cfg(test)]
 use super::card::Suit;
+#[cfg(test)]
+use crate::card::parse_cards;
+#[cfg(test)]
+use std::str::FromStr;
+
+/// A hand with its suits relabeled into a standard order, so that hands which differ only by which
+/// suit is which come out equal.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CanonicalHand {
+    /// The held cards in deck order, followed by the starter if there is one.
+    pub cards: Vec<Card>,
+    pub has_starter: bool,
+    /// How many different hands, including this one, are the same as it apart from the suits.
+    pub multiplicity: u32,
+}
+
+impl CanonicalHand {
+    pub fn hand(&self) -> Hand<'_> {
+        if self.has_starter {
+            Hand::with_starter(&self.cards)
+        } else {
+            Hand::new(&self.cards)
+        }
+    }
+}
 
 pub struct Hand<'a> {
+    /// All the cards in the hand, including the starter if there is one.
     pub cards: &'a [Card],
+    pub starter: Option<&'a Card>,
+    /// Whether this is the crib, where a flush only counts if the starter is the same suit too.
+    pub crib: bool,
 }
 
 impl<'a> Hand<'a> {
     pub fn new(cards: &'a [Card]) -> Hand<'a> {
         Hand {
             cards,
+            starter: None,
+            crib: false,
+        }
+    }
+
+    /// Makes a hand where the last card is the starter (the cut card).
+    pub fn with_starter(cards: &'a [Card]) -> Hand<'a> {
+        Hand {
+            cards,
+            starter: cards.last(),
+            crib: false,
+        }
+    }
+
+    /// Makes a crib, where the last card is the starter.
+    pub fn crib(cards: &'a [Card]) -> Hand<'a> {
+        Hand {
+            crib: true,
+            .. Hand::with_starter(cards)
+        }
+    }
+
+    /// The cards held in the hand, not counting the starter.
+    pub fn held_cards(&self) -> &'a [Card] {
+        match self.starter {
+            Some(_) => &self.cards[.. self.cards.len() - 1],
+            None => self.cards,
         }
     }
 
     fn find_fifteens(&self, combos: &mut Vec<Combo<'a>>) {
         for set in util::power_set(self.cards) {
             if set.iter().fold(0, |acc, card| acc + card.value()) == 15 {
-                combos.push(Combo {
-                    cards: set.clone(),
-                    score: 2,
-                    text: "fifteen".to_owned(),
-                });
+                combos.push(Combo::new(set, ComboKind::Fifteen));
             }
         }
     }
@@ -39,29 +93,56 @@ impl<'a> Hand<'a> {
         let by_number = util::group_by(self.cards, |card| card.number);
         for group in by_number.values() {
             if group.len() > 1 {
-                combos.push(Combo {
-                    cards: group.clone(),
-                    //score: 2 * (util::factorial(group.len() as i8) / 4), // n take 2 = n! / (2!)^2
-                    score: 2 * util::binomial_coefficient(group.len() as i8, 2),
-                    text: format!("{} of a kind", group.len()),
-                });
+                combos.push(Combo::new(group.clone(), ComboKind::of_a_kind(group.len())));
             }
         }
     }
 
     fn find_flush(&self, combos: &mut Vec<Combo<'a>>) {
+        if let Some(starter) = self.starter {
+            // With a starter, the held cards must all be the same suit, and the starter only adds
+            // to the flush. In the crib, the starter has to match as well.
+            let held = self.held_cards();
+            if held.len() >= 3 && held.iter().all(|card| card.suit == held[0].suit)
+                && (!self.crib || starter.suit == held[0].suit)
+            {
+                let mut cards: Vec<&'a Card> = held.iter().collect();
+                if starter.suit == held[0].suit {
+                    cards.push(starter);
+                }
+                let n = cards.len();
+                combos.push(Combo::new(cards, ComboKind::Flush(n)));
+            }
+            return;
+        }
+
         let by_suit = util::group_by(self.cards, |card| card.suit);
         for group in by_suit.values() {
             if group.len() > 4 {
-                combos.push(Combo {
-                    cards: group.clone(),
-                    score: group.len() as i8,
-                    text: format!("{}-flush", group.len()),
-                });
+                combos.push(Combo::new(group.clone(), ComboKind::Flush(group.len())));
+            }
+        }
+    }
+
+    fn find_nobs(&self, combos: &mut Vec<Combo<'a>>) {
+        if let Some(starter) = self.starter {
+            for card in self.held_cards() {
+                if card.number == 11 && card.suit == starter.suit {
+                    combos.push(Combo::new(vec![card], ComboKind::Nobs));
+                }
             }
         }
     }
 
+    /// His heels: the starter, if it's a jack. It isn't part of the count, since the dealer pegs it
+    /// as soon as it's cut.
+    pub fn heels(&self) -> Option<Combo<'a>> {
+        self.starter
+            .filter(|starter| starter.number == 11)
+            .map(|starter| Combo::new(vec![starter], ComboKind::Heels))
+    }
+
+    #[allow(clippy::same_item_push)]
     fn make_run_combos(cards: &[Vec<&'a Card>], combos: &mut Vec<Combo<'a>>) {
         let mut indices: Vec<usize> = vec![];
         for _ in 0 .. cards.len() { indices.push(0); }
@@ -71,11 +152,7 @@ impl<'a> Hand<'a> {
                 combo_cards.push(cards[i][*idx]);
             }
 
-            combos.push(Combo {
-                cards: combo_cards,
-                score: cards.len() as i8,
-                text: format!("run of {}", cards.len()),
-            });
+            combos.push(Combo::new(combo_cards, ComboKind::Run(cards.len())));
 
             for i in 0 ..= cards.len() {
                 if i == cards.len() {
@@ -118,18 +195,55 @@ impl<'a> Hand<'a> {
         self.find_n_of_kind(&mut combos);
         self.find_flush(&mut combos);
         self.find_runs(&mut combos);
+        self.find_nobs(&mut combos);
         combos
     }
+
+    /// Relabels the suits into a standard order. Doing the same thing to every card, including the
+    /// starter, doesn't change the score.
+    pub fn canonical(&self) -> CanonicalHand {
+        let held = CardSet::from_cards(self.held_cards());
+        let signature = |suit| packed::suit_signature(held, self.starter, suit);
+        let to = card::canonical_suits(signature);
+
+        let mut cards = held.relabel(&to).to_cards();
+        if let Some(starter) = self.starter {
+            cards.push(Card { number: starter.number, suit: to[starter.suit as usize] });
+        }
+        CanonicalHand {
+            cards,
+            has_starter: self.starter.is_some(),
+            multiplicity: card::suit_multiplicity(signature),
+        }
+    }
+
+    /// Total points in the hand. If the score table is enabled, four cards plus a starter are
+    /// looked up there instead of finding all the combos, unless it's the crib.
+    pub fn score(&self) -> i32 {
+        if let (Some(table), Some(starter)) = (table::get(), self.starter) {
+            let held = self.held_cards();
+            if held.len() == 4 && !self.crib {
+                if let Some(score) = table.score(CardSet::from_cards(held), starter) {
+                    return i32::from(score);
+                }
+            }
+        }
+        self.find_all_combos().iter().fold(0, |score, combo| score + i32::from(combo.score))
+    }
 }
 
 impl<'a> Display for Hand<'a> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
-        for (idx, card) in self.cards.iter().enumerate() {
+        let held = self.held_cards();
+        for (idx, card) in held.iter().enumerate() {
             card.fmt(fmt)?;
-            if idx < self.cards.len() - 1 {
+            if idx < held.len() - 1 {
                 fmt.write_str(" ").unwrap();
             }
         }
+        if let Some(starter) = self.starter {
+            write!(fmt, " | {}", starter)?;
+        }
         Ok(())
     }
 }
@@ -165,3 +279,194 @@ fn test_find_runs() {
 
 
 }
+
+#[test]
+fn test_starter() {
+    let cards: Vec<Card> = ["jh", "2h", "6h", "9h", "3h"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let hand = Hand::with_starter(&cards);
+    assert_eq!(format!("{}", hand), "JH 2H 6H 9H | 3H");
+    let kinds: Vec<ComboKind> = hand.find_all_combos().iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, vec![
+        ComboKind::Fifteen, ComboKind::Fifteen, ComboKind::Flush(5), ComboKind::Nobs]);
+
+    let cards: Vec<Card> = ["jh", "2h", "6h", "9h", "3s"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let hand = Hand::with_starter(&cards);
+    let kinds: Vec<ComboKind> = hand.find_all_combos().iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, vec![ComboKind::Fifteen, ComboKind::Fifteen, ComboKind::Flush(4)]);
+
+    // Without a starter, four of a suit isn't enough.
+    let hand = Hand::new(&cards);
+    assert!(hand.find_all_combos().iter().all(|c| c.kind != ComboKind::Flush(4)));
+
+    // Nor is it in the crib, unless the starter matches.
+    let crib = Hand::crib(&cards);
+    assert!(crib.find_all_combos().iter().all(|c| c.kind != ComboKind::Flush(4)));
+    assert_eq!(crib.score(), 4);
+
+    // A starter jack is his heels, which isn't counted with the hand.
+    assert!(hand.heels().is_none());
+    let cards: Vec<Card> = ["jh", "2h", "6h", "9h", "js"].iter()
+        .map(|s| Card::from_str(s).unwrap())
+        .collect();
+    let hand = Hand::with_starter(&cards);
+    let heels = hand.heels().unwrap();
+    assert_eq!((heels.kind, heels.score), (ComboKind::Heels, 2));
+    assert_eq!(heels.kind.to_string(), "his heels");
+    assert!(hand.find_all_combos().iter().all(|c| c.kind != ComboKind::Heels));
+}
+
+#[test]
+fn test_hand_sizes() {
+    let score = |s: &str| -> i32 {
+        let cards = parse_cards(s);
+        Hand::with_starter(&cards).score()
+    };
+
+    // Five-card cribbage keeps three cards, which can make a flush on their own.
+    assert_eq!(score("2h 4h 6h ks"), 3);
+    assert_eq!(score("2h 4h 6h kh"), 4);
+    assert_eq!(score("5h 5c 5s 5d"), 12 + 8);
+
+    // Seven-card cribbage keeps five.
+    assert_eq!(score("2h 4h 6h 8h qh ks"), 5);
+    assert_eq!(score("ah 2c 3d 4s 5h 6c"), 6 + 8);
+}
+
+#[test]
+fn test_canonical() {
+    let a = parse_cards("5h 6h 7c jd 5d");
+    let b = parse_cards("7s 6d 5d jh 5h");
+    let canonical = Hand::with_starter(&a).canonical();
+    assert_eq!(canonical, Hand::with_starter(&b).canonical());
+    assert_eq!(format!("{}", canonical.hand()), "5H 6H 7C JS | 5S");
+    assert_eq!(canonical.hand().score(), Hand::with_starter(&a).score());
+    // Every suit is different: one has the jack and the starter, one the 7, one the 5 and 6, and
+    // the last has nothing.
+    assert_eq!(canonical.multiplicity, 24);
+
+    // Without the starter, the nobs doesn't count, and the jack is just another card.
+    let c = parse_cards("5h 6h 7c jd 5s");
+    assert_ne!(Hand::with_starter(&c).canonical(), canonical);
+
+    let flush = parse_cards("ah 3h 5h 7h");
+    assert_eq!(Hand::new(&flush).canonical().multiplicity, 4);
+}
+
+#[test]
+fn test_golden_scores() {
+    let score = |s: &str| -> i32 {
+        let cards = parse_cards(s);
+        Hand::with_starter(&cards).score()
+    };
+
+    // The perfect hand: the jack held matches the five cut, for nobs.
+    assert_eq!(score("5h 5c 5s jd 5d"), 29);
+
+    // Four fives and a ten-card, or three fives and a ten-card with the fourth five cut (as long as
+    // it isn't his nobs).
+    assert_eq!(score("5h 5c 5s 5d 10h"), 28);
+    assert_eq!(score("5h 5c 5s 5d jh"), 28);
+    assert_eq!(score("5h 5c 5s 5d kc"), 28);
+    assert_eq!(score("5h 5c 5s qd 5d"), 28);
+    assert_eq!(score("5h 5c 5s jh 5d"), 28);
+
+    // Double double runs where every run also makes fifteen.
+    assert_eq!(score("4h 4c 5s 5d 6h"), 24);
+    assert_eq!(score("4h 5c 5s 6d 6h"), 24);
+    assert_eq!(score("7h 7c 8s 8d 9h"), 24);
+    // And the other way to get 24.
+    assert_eq!(score("3h 3c 3s 3d 9h"), 24);
+
+    assert_eq!(score("2h 4c 6s 8d 10h"), 0);
+    assert_eq!(score("kh qc 9s 7d 2h"), 0);
+    assert_eq!(score("ah 3h 5h 7h jh"), 9);
+    assert_eq!(score("ah 3h 5h 7h jc"), 8);
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::card::Suit;
+    use crate::combo::Category;
+
+    /// Four held cards and a starter, dealt from a deck.
+    fn show() -> impl Strategy<Value = Vec<Card>> {
+        proptest::sample::subsequence((0 .. 52).collect::<Vec<usize>>(), 5)
+            .prop_shuffle()
+            .prop_map(|indices| indices.into_iter().map(Card::from_index).collect())
+    }
+
+    /// The combos that don't depend on the suits.
+    fn suitless_score(hand: &Hand<'_>) -> i32 {
+        hand.find_all_combos().iter()
+            .filter(|combo| combo.category() != Category::Flush && combo.category() != Category::Nobs)
+            .fold(0, |score, combo| score + i32::from(combo.score))
+    }
+
+    proptest! {
+        #[test]
+        fn test_score_in_range(cards in show()) {
+            let score = Hand::with_starter(&cards).score();
+            prop_assert!((0 ..= 29).contains(&score));
+            prop_assert!(![19, 25, 26, 27].contains(&score));
+        }
+
+        #[test]
+        fn test_combos_use_hand_cards(cards in show()) {
+            let hand = Hand::with_starter(&cards);
+            let combos = hand.find_all_combos();
+            for (i, combo) in combos.iter().enumerate() {
+                prop_assert!(!combo.cards.is_empty());
+                for (j, card) in combo.cards.iter().enumerate() {
+                    prop_assert!(cards.contains(card));
+                    prop_assert!(!combo.cards[.. j].contains(card), "{} is in {:?} twice", card, combo);
+                }
+                for other in &combos[.. i] {
+                    prop_assert!(other.kind != combo.kind || other != &combo.cards[..],
+                        "{:?} is there twice", combo);
+                }
+            }
+        }
+
+        #[test]
+        fn test_score_ignores_order(cards in show(), order in Just(vec![0, 1, 2, 3]).prop_shuffle()) {
+            let mut shuffled: Vec<Card> = order.iter().map(|&i| cards[i]).collect();
+            shuffled.push(cards[4]);
+            prop_assert_eq!(Hand::with_starter(&shuffled).score(), Hand::with_starter(&cards).score());
+        }
+
+        #[test]
+        fn test_score_ignores_suit_names(
+            cards in show(),
+            to in Just(Suit::ALL.to_vec()).prop_shuffle(),
+        ) {
+            let relabeled: Vec<Card> = cards.iter()
+                .map(|card| Card { number: card.number, suit: to[card.suit as usize] })
+                .collect();
+            prop_assert_eq!(Hand::with_starter(&relabeled).score(), Hand::with_starter(&cards).score());
+        }
+
+        #[test]
+        fn test_only_flush_and_nobs_need_suits(
+            cards in show(),
+            suits in proptest::collection::vec(0 .. 4usize, 5),
+        ) {
+            let resuited: Vec<Card> = cards.iter()
+                .zip(suits)
+                .map(|(card, suit)| Card { number: card.number, suit: Suit::ALL[suit] })
+                .collect();
+            for (i, card) in resuited.iter().enumerate() {
+                prop_assume!(!resuited[.. i].contains(card));
+            }
+            prop_assert_eq!(
+                suitless_score(&Hand::with_starter(&resuited)),
+                suitless_score(&Hand::with_starter(&cards)));
+        }
+    }
+}
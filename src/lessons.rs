@@ -0,0 +1,211 @@
+// Lessons :: a course that takes a new player through the combos one kind at a time.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// Each unit explains something, then deals a few hands that have plenty of it. Count enough of them
+// perfectly and the next unit opens up. Progress is kept for each player by name.
+//
+
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::combo::Category;
+use crate::generate::Constraint;
+use crate::stats;
+
+pub struct Unit {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    /// The only category that counts in the exercises, or `None` to count everything.
+    pub drill: Option<Category>,
+    /// What the exercise hands have to have.
+    pub constraints: &'static [Constraint],
+    pub exercises: usize,
+    /// How many exercises have to be counted perfectly to pass.
+    pub pass: usize,
+}
+
+pub static UNITS: [Unit; 8] = [
+    Unit {
+        name: "fifteens",
+        title: "Fifteens with two cards",
+        explanation: "Any cards that add up to 15 score 2 points, called \"fifteen two\". Aces count 1, \
+            and tens, jacks, queens and kings all count 10, so look for a 5 with any ten-card, and for \
+            6 and 9, 7 and 8. Each different set of cards counts separately, so a 5 with two kings is \
+            two fifteens.",
+        drill: Some(Category::Fifteens),
+        constraints: &[Constraint::FifteenSizes(2 ..= 2)],
+        exercises: 5,
+        pass: 4,
+    },
+    Unit {
+        name: "long-fifteens",
+        title: "Fifteens with more cards",
+        explanation: "Fifteens can use three, four, or even all five cards, like 2 + 3 + 10 or \
+            A + 4 + K or 2 + 3 + 4 + 6. These are the ones that get missed. Try going through the \
+            small cards and seeing what's left to make 15.",
+        drill: Some(Category::Fifteens),
+        constraints: &[Constraint::FifteenSizes(3 ..= 5)],
+        exercises: 5,
+        pass: 4,
+    },
+    Unit {
+        name: "pairs",
+        title: "Pairs and pair royals",
+        explanation: "Two cards of the same number are a pair, for 2. Three of a kind is a pair royal, \
+            for 6, because it's three different pairs; four of a kind is a double pair royal, for 12.",
+        drill: Some(Category::Pairs),
+        constraints: &[Constraint::Category(Category::Pairs, 2)],
+        exercises: 5,
+        pass: 4,
+    },
+    Unit {
+        name: "runs",
+        title: "Runs",
+        explanation: "Three or more numbers in a row, like 4-5-6, are a run, worth a point for each card. \
+            Suits don't matter, and aces are always low, so Q-K-A isn't a run.",
+        drill: Some(Category::Runs),
+        constraints: &[Constraint::Category(Category::Runs, 3)],
+        exercises: 5,
+        pass: 4,
+    },
+    Unit {
+        name: "double-runs",
+        title: "Double runs",
+        explanation: "If one of the numbers in a run is doubled, like 4-5-5-6, there are two runs, one \
+            with each five, plus the pair: a double run of 3 is 8 points. Count everything in these \
+            hands, fifteens included.",
+        drill: None,
+        constraints: &[Constraint::DoubleRun],
+        exercises: 5,
+        pass: 3,
+    },
+    Unit {
+        name: "flush",
+        title: "Flushes",
+        explanation: "If all four cards in your hand are the same suit, that's a flush for 4, or 5 if \
+            the starter matches too. The starter can't make a flush on its own with three of your cards. \
+            In the crib, it only counts if all five cards match.",
+        drill: Some(Category::Flush),
+        constraints: &[Constraint::Flush],
+        exercises: 3,
+        pass: 3,
+    },
+    Unit {
+        name: "nobs",
+        title: "His nobs",
+        explanation: "A jack in your hand of the same suit as the starter is \"his nobs\", for 1 point. \
+            The starter itself being a jack doesn't count for nobs.",
+        drill: Some(Category::Nobs),
+        constraints: &[Constraint::Nobs],
+        exercises: 3,
+        pass: 3,
+    },
+    Unit {
+        name: "full",
+        title: "The whole hand",
+        explanation: "Now put it all together: fifteens, pairs, runs, flushes and nobs.",
+        drill: None,
+        constraints: &[Constraint::Score(4 ..= 29)],
+        exercises: 10,
+        pass: 7,
+    },
+];
+
+pub fn find_unit(name: &str) -> Option<&'static Unit> {
+    UNITS.iter().find(|unit| unit.name == name)
+}
+
+/// Which units a player has passed.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Progress {
+    pub passed: BTreeSet<String>,
+}
+
+impl Progress {
+    /// The first unit not passed yet, or `None` once they all are.
+    pub fn next_unit(&self) -> Option<&'static Unit> {
+        UNITS.iter().find(|unit| !self.passed.contains(unit.name))
+    }
+
+    /// A unit is open once every one before it has been passed.
+    pub fn unlocked(&self, unit: &Unit) -> bool {
+        UNITS.iter()
+            .take_while(|x| x.name != unit.name)
+            .all(|x| self.passed.contains(x.name))
+    }
+
+    pub fn pass(&mut self, unit: &Unit) {
+        self.passed.insert(unit.name.to_owned());
+    }
+
+    pub fn load(path: &Path) -> io::Result<Progress> {
+        let mut progress = Progress::default();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if find_unit(line.trim()).is_some() {
+                progress.passed.insert(line.trim().to_owned());
+            }
+        }
+        Ok(progress)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(path)?;
+        for name in &self.passed {
+            writeln!(file, "{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a player's progress is kept.
+pub fn progress_path(player: &str) -> Option<PathBuf> {
+    let file: String = player.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    stats::data_dir().map(|dir| dir.join("lessons").join(format!("{}.txt", file)))
+}
+
+#[test]
+fn test_progress() {
+    use crate::generate::Generator;
+    use crate::hand::Hand;
+    use crate::rules::Rules;
+
+    // Every unit can come up with its exercises.
+    let mut generator = Generator::new(3, Rules::STANDARD);
+    for unit in &UNITS {
+        assert!(unit.pass <= unit.exercises);
+        let cards = generator.hand(unit.constraints).unwrap();
+        let hand = Hand::with_starter(&cards);
+        if let Some(category) = unit.drill {
+            assert!(hand.find_all_combos().iter().any(|combo| combo.category() == category), "{}", unit.name);
+        }
+    }
+
+    let mut progress = Progress::default();
+    assert_eq!(progress.next_unit().unwrap().name, "fifteens");
+    assert!(!progress.unlocked(find_unit("pairs").unwrap()));
+    progress.pass(&UNITS[0]);
+    progress.pass(&UNITS[1]);
+    assert_eq!(progress.next_unit().unwrap().name, "pairs");
+    assert!(progress.unlocked(find_unit("pairs").unwrap()));
+    assert!(!progress.unlocked(find_unit("runs").unwrap()));
+
+    let path = std::env::temp_dir().join(format!("cribbagepractice-lessons-{}.txt", std::process::id()));
+    progress.save(&path).unwrap();
+    let loaded = Progress::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, progress);
+
+    if let Some(path) = progress_path("a/b") {
+        assert!(path.ends_with("lessons/a_b.txt"));
+    }
+}
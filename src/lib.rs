@@ -0,0 +1,28 @@
+#![deny(rust_2018_idioms)]
+
+// Cribbage hand scoring and the practice game, independent of any particular user interface.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+pub mod analyze;
+pub mod card;
+pub mod catch;
+pub mod choice;
+pub mod combo;
+pub mod cross;
+pub mod deck;
+pub mod discard;
+pub mod explain;
+pub mod game;
+pub mod generate;
+pub mod hand;
+pub mod lessons;
+pub mod packed;
+pub mod rules;
+pub mod spoken;
+pub mod starters;
+pub mod stats;
+pub mod table;
+pub mod ui;
+pub mod util;
@@ -9,62 +9,80 @@ use std::cell::RefCell;
 use std::io::{self, Write};
 use std::str::FromStr;
 
-mod card;
-mod combo;
-mod deck;
-mod game;
-mod hand;
-mod ui;
-mod util;
-
-use crate::card::{Card, CardParseError};
-use crate::combo::Combo;
-use crate::deck::Deck;
-use crate::game::Game;
-use crate::hand::Hand;
-use crate::ui::{UserInterface, Guess};
+use cribbagepractice::analyze;
+use cribbagepractice::card::{self, Card, CardParseError};
+use cribbagepractice::catch::{self, Verdict};
+use cribbagepractice::choice::{self, Question};
+use cribbagepractice::combo::{self, Category, Combo, ComboKind};
+use cribbagepractice::cross::{self, CrossGrid, Line};
+use cribbagepractice::deck::Deck;
+use cribbagepractice::discard;
+use cribbagepractice::explain;
+use cribbagepractice::generate::{Constraint, Generator};
+use cribbagepractice::game::{Game, GuessStyle, Mode, PairStyle, Settings};
+use cribbagepractice::hand::Hand;
+use cribbagepractice::lessons::{self, Progress, Unit};
+use cribbagepractice::rules::Rules;
+use cribbagepractice::spoken::{self, ComponentResult, SpokenCount};
+use cribbagepractice::starters::{self, NumberScore, StarterQuestion};
+use cribbagepractice::stats::{self, Stats};
+use cribbagepractice::table::{self, ScoreTable};
+use cribbagepractice::ui::{UserInterface, Action, CategoryClaim, Guess, Hint};
 
 struct ConsoleUI {
     pub player_score: i32,
     pub cpu_score: i32,
+    /// Missed points are pegged against the player rather than going to the computer.
+    pub lowball: bool,
 }
 
 impl UserInterface for ConsoleUI {
     fn display_hand(&mut self, hand: &Hand<'_>) {
-        println!("{}", hand);
+        if hand.crib {
+            println!("Your crib: {}", hand);
+        } else {
+            println!("{}", hand);
+        }
     }
 
-    fn display_missed_combos(&mut self, combos: &[Combo<'_>]) {
+    fn display_missed_combos(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]) {
         println!("You missed some:");
-        for combo in combos {
-            for (idx, card) in combo.cards.iter().enumerate() {
-                print!("{}", card);
-                if idx < combo.cards.len() - 1 {
-                    print!(" ");
-                }
-            }
-            println!(": {} points for a {}", combo.score, combo.text);
-        }
+        print_explained_combos(hand, combos);
     }
 
     fn display_win_message(&mut self, score: i32) {
-        println!("Aww yiss! {} points for you!", score);
+        if self.lowball {
+            println!("All counted. {} points are pegged against you.", score);
+        } else {
+            println!("Aww yiss! {} points for you!", score);
+        }
     }
 
     fn display_lose_message(&mut self, score: i32) {
-        println!("Computer gets muggins of {} points.", score);
+        if self.lowball {
+            println!("Muggins! {} points are pegged against you.", score);
+        } else {
+            println!("Computer gets muggins of {} points.", score);
+        }
     }
 
     fn display_bad_guess_wrong_score(&mut self, actual_combo: &Combo<'_>) {
-        println!("Nope, score is {} for a {}.", actual_combo.score, actual_combo.text);
+        println!("Nope, score is {} for a {}.", actual_combo.score, actual_combo.kind);
     }
 
-    fn display_bad_guess_invalid_combo(&mut self) {
+    fn display_bad_guess_invalid_combo(&mut self, reasons: &[String]) {
         println!("Nope! That's nothing.");
+        for reason in reasons {
+            println!("  {}", reason);
+        }
     }
 
     fn display_correct_guess(&mut self, combo: &Combo<'_>) {
-        println!("Correct! {} points for a {}.", combo.score, combo.text);
+        println!("Correct! {} points for a {}.", combo.score, combo.kind);
+    }
+
+    fn display_outside_drill(&mut self, category: Category, drill: Category) {
+        println!("That's {}, but only {} count in this drill.", category, drill);
     }
 
     fn add_score_player(&mut self, score: i32) {
@@ -75,7 +93,289 @@ impl UserInterface for ConsoleUI {
         self.cpu_score += score;
     }
 
-    fn get_guess(&mut self, hand: &Hand<'_>) -> Option<Guess> {
+    fn display_category_result(&mut self, category: Category, claimed: i8, actual: i8) {
+        if claimed == actual {
+            println!("Correct! {} for {}.", category, actual);
+        } else if claimed > actual {
+            println!("Too many! You claimed {} for {}, but there's only {}.", claimed, category, actual);
+        } else {
+            println!("Too few! You claimed {} for {}, but there's {}.", claimed, category, actual);
+        }
+    }
+
+    fn display_hint(&mut self, hint: &Hint, cost: i32) {
+        match hint {
+            Hint::Count(1) => println!("There's 1 combo left."),
+            Hint::Count(n) => println!("There are {} combos left.", n),
+            Hint::Categories(counts) => {
+                let counts: Vec<String> = counts.iter()
+                    .map(|(category, n)| format!("{} {}", n, category))
+                    .collect();
+                println!("Left to count: {}.", counts.join(", "));
+            },
+            Hint::Card(card, kind) => println!("{} is part of a {}.", card, kind),
+        }
+        println!("(That hint cost you {}.)", cost);
+    }
+
+    fn display_undo(&mut self, guess: Option<&Guess>) {
+        match guess {
+            Some(guess) => {
+                let cards: Vec<String> = guess.cards.iter().map(Card::to_string).collect();
+                println!("Took back \"{} {}\".", cards.join(" "), guess.score);
+            },
+            None => println!("Nothing to take back."),
+        }
+    }
+
+    fn display_pone_bonus(&mut self, points: i32) {
+        println!("The computer deals first, so you get {} points to start with.", points);
+    }
+
+    fn display_cpu_hand(&mut self, hand: &Hand<'_>, score: i32) {
+        println!("Computer's hand: {}", hand);
+        println!("Computer counts {}.", score);
+    }
+
+    fn display_cross(&mut self, grid: &CrossGrid, line: Option<Line>) {
+        if let Some(Line::Column(column)) = line {
+            println!("  {}v", "    ".repeat(column));
+        }
+        for row in 0 .. cross::SIZE {
+            let marker = if line == Some(Line::Row(row)) { ">" } else { " " };
+            let cards: Vec<String> = (0 .. cross::SIZE)
+                .map(|column| format!("{:<4}", grid.card(row, column).to_string()))
+                .collect();
+            println!("{} {}", marker, cards.join("").trim_end());
+        }
+        match line {
+            Some(line) => print!("Count {}: ", line),
+            None => print!("Count the crib: "),
+        }
+    }
+
+    fn display_cpu_announcement(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]) {
+        println!("Computer's hand: {}", hand);
+        for combo in combos {
+            println!("  {}: {} for a {}", combo_str(combo), combo.score, combo.kind);
+        }
+        let total = combos.iter().fold(0, |total, combo| total + i32::from(combo.score));
+        println!("Computer counts {}.", total);
+        println!("Press enter to accept, or call muggins by listing the cards of each wrong or missing");
+        println!("combo, separated by commas.");
+    }
+
+    fn get_muggins_calls(&mut self, hand: &Hand<'_>) -> Vec<Vec<Card>> {
+        let mut line = String::new();
+        'input: loop {
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+
+            let mut calls = vec![];
+            for part in line.split(',') {
+                if part.trim().is_empty() {
+                    continue;
+                }
+                match parse_cards(part) {
+                    Ok(cards) => {
+                        if let Some(card) = cards.iter().find(|card| !hand.cards.contains(card)) {
+                            println!("{} isn't in the computer's hand!", card);
+                            continue 'input;
+                        }
+                        calls.push(cards);
+                    },
+                    Err(e) => {
+                        println!("{}", e);
+                        continue 'input;
+                    }
+                }
+            }
+            return calls;
+        }
+    }
+
+    fn display_verdict(&mut self, hand: &Hand<'_>, verdict: &Verdict<'_>) {
+        for combo in &verdict.caught_missed {
+            println!("Muggins! The computer missed {}: {} points for you.", combo_str(combo), combo.score);
+        }
+        for combo in &verdict.caught_bogus {
+            if catch::counted_twice(hand, combo) {
+                println!("Caught it! {} was already counted.", combo_str(combo));
+            } else {
+                let cards: Vec<Card> = combo.cards.iter().map(|card| **card).collect();
+                let reasons = explain::explain_rejection(hand, &cards, &[]);
+                println!("Caught it! {} isn't a {}. {}", combo_str(combo), combo.kind, reasons.join(" "));
+            }
+        }
+        for cards in &verdict.wrong_calls {
+            let cards: Vec<String> = cards.iter().map(Card::to_string).collect();
+            println!("{} wasn't a mistake.", cards.join(" "));
+        }
+        for combo in &verdict.uncaught_missed {
+            println!("You didn't catch it: the computer missed {} for a {}.", combo_str(combo), combo.kind);
+        }
+        for combo in &verdict.uncaught_bogus {
+            if catch::counted_twice(hand, combo) {
+                println!("You didn't catch it: {} was counted twice.", combo_str(combo));
+            } else {
+                println!("You didn't catch it: {} isn't a {}.", combo_str(combo), combo.kind);
+            }
+        }
+        if verdict.caught_missed.is_empty() && verdict.caught_bogus.is_empty()
+            && verdict.wrong_calls.is_empty() && verdict.uncaught_missed.is_empty()
+            && verdict.uncaught_bogus.is_empty()
+        {
+            println!("Right, the computer counted correctly.");
+        }
+    }
+
+    fn get_choice(&mut self, _hand: &Hand<'_>, question: &Question<'_>) -> usize {
+        match question {
+            Question::Total { choices, .. } => {
+                println!("What's the total?");
+                for (i, score) in choices.iter().enumerate() {
+                    println!("  {}) {}", i + 1, score);
+                }
+            },
+            Question::Decoy { choices, .. } => {
+                println!("Which one of these doesn't score?");
+                for (i, combo) in choices.iter().enumerate() {
+                    println!("  {}) {}: {} for a {}", i + 1, combo_str(combo), combo.score, combo.kind);
+                }
+            },
+        }
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+            match line.trim().parse::<usize>() {
+                Ok(n) if (1 ..= choice::CHOICES).contains(&n) => return n - 1,
+                _ => println!("Pick a number from 1 to {}.", choice::CHOICES),
+            }
+        }
+    }
+
+    fn display_choice_result(&mut self, hand: &Hand<'_>, question: &Question<'_>, chosen: usize) {
+        let right = chosen == question.answer();
+        match question {
+            Question::Total { choices, answer } => {
+                if right {
+                    println!("Correct! {} points for you.", choices[*answer]);
+                } else {
+                    println!("Nope, it's {}:", choices[*answer]);
+                    print_explained_combos(hand, &hand.find_all_combos());
+                }
+            },
+            Question::Decoy { choices, answer } => {
+                let decoy = &choices[*answer];
+                let cards: Vec<Card> = decoy.cards.iter().map(|card| **card).collect();
+                let reasons = explain::explain_rejection(hand, &cards, &[]);
+                if right {
+                    println!("Correct! {}", reasons.join(" "));
+                } else {
+                    println!("Nope, that one's real. It's {} that doesn't score: {}",
+                        combo_str(decoy), reasons.join(" "));
+                }
+            },
+        }
+    }
+
+    fn display_lesson(&mut self, unit: &Unit) {
+        println!("{}", unit.title);
+        println!("{}", unit.explanation);
+        println!("Count at least {} of the next {} hands perfectly to pass.", unit.pass, unit.exercises);
+    }
+
+    fn display_lesson_result(&mut self, unit: &Unit, perfect: usize) {
+        println!();
+        println!("You counted {} of {} hands perfectly.", perfect, unit.exercises);
+    }
+
+    fn get_starter_answer(&mut self, held: &[Card], question: &StarterQuestion) -> Vec<i8> {
+        let cards: Vec<String> = held.iter().map(Card::to_string).collect();
+        println!("You kept: {}", cards.join(" "));
+        match question {
+            StarterQuestion::Best => println!("Which starter would score the most?"),
+            StarterQuestion::AtLeast(points) => {
+                println!("Which starters could give you at least {} points? Name them all.", points)
+            },
+        }
+        let mut line = String::new();
+        loop {
+            print!("> ");
+            io::stdout().flush().unwrap();
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+            let numbers: Option<Vec<i8>> = line.split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|word| !word.is_empty())
+                .map(card::number_from_symbol)
+                .collect();
+            match numbers {
+                Some(numbers) => return numbers,
+                None => println!("Give card numbers, like \"5\" or \"4 6 J\"."),
+            }
+        }
+    }
+
+    fn display_starter_result(&mut self, _held: &[Card], question: &StarterQuestion, numbers: &[NumberScore],
+            right: bool) {
+        let answer: Vec<String> = question.answer(numbers).into_iter().map(card::number_symbol).collect();
+        match (right, question) {
+            (true, _) => println!("Correct!"),
+            (false, StarterQuestion::Best) => println!("Nope, the best is {}.", answer.join(" or ")),
+            (false, StarterQuestion::AtLeast(points)) => {
+                println!("Nope, {} can give at least {}.", answer.join(", "), points)
+            },
+        }
+        for number in numbers {
+            if number.best == number.worst {
+                println!("  {:>2}: {}", card::number_symbol(number.number), number.best);
+            } else {
+                println!("  {:>2}: {} to {}, depending on the suit",
+                    card::number_symbol(number.number), number.worst, number.best);
+            }
+        }
+    }
+
+    fn get_declared_total(&mut self, _hand: &Hand<'_>) -> Option<i32> {
+        let mut line = String::new();
+        loop {
+            print!("Your total? ");
+            io::stdout().flush().unwrap();
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            match trimmed.parse() {
+                Ok(total) => return Some(total),
+                Err(_) => println!("invalid number"),
+            }
+        }
+    }
+
+    fn display_declared_total(&mut self, declared: i32, counted: i32) {
+        if declared > counted {
+            println!("You only counted {}, so that's all you get.", counted);
+        } else {
+            println!("You counted {}, but only said {}, so that's all you get.", counted, declared);
+        }
+    }
+
+    fn display_muggins(&mut self, hand: &Hand<'_>, combo: &Combo<'_>) {
+        let who = if self.lowball { "pegged against you" } else { "for the computer" };
+        println!("Muggins! {}: {} points {} for a {} ({})",
+            combo_str(combo), combo.score, who, combo.kind, explain::explain_combo(combo, hand));
+    }
+
+    fn display_unnoticed(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]) {
+        println!("The computer didn't notice you also missed:");
+        print_explained_combos(hand, combos);
+    }
+
+    fn get_action(&mut self, hand: &Hand<'_>) -> Action {
         let mut guess = Guess {
             cards: vec![],
             score: 0,
@@ -90,6 +390,12 @@ impl UserInterface for ConsoleUI {
             if trimmed.is_empty() {
                 break;
             }
+            if trimmed.eq_ignore_ascii_case("hint") {
+                return Action::Hint;
+            }
+            if trimmed.eq_ignore_ascii_case("undo") {
+                return Action::Undo;
+            }
 
             let mut parts: Vec<&str> = trimmed.split(' ').collect();
             if let Ok(score) = parts.iter().last().unwrap().parse::<i8>() {
@@ -141,11 +447,90 @@ impl UserInterface for ConsoleUI {
         }
 
         if guess.cards.is_empty() {
-            None
+            Action::Done
         } else {
-            Some(guess)
+            Action::Guess(guess)
         }
     }
+
+    fn get_category_claims(&mut self, _hand: &Hand<'_>) -> Vec<CategoryClaim> {
+        let mut line = String::new();
+        'input: loop {
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+
+            let mut claims = vec![];
+            for part in line.split(',') {
+                let part = part.trim();
+                if part.is_empty() {
+                    continue;
+                }
+
+                let words: Vec<&str> = part.split_whitespace().collect();
+                if words.len() != 2 {
+                    println!("Claims look like \"fifteens 6, pairs 2, runs 3\".");
+                    continue 'input;
+                }
+
+                let category = match Category::from_name(words[0]) {
+                    Some(category) => category,
+                    None => {
+                        println!("I don't know what {:?} are.", words[0]);
+                        continue 'input;
+                    }
+                };
+
+                match words[1].parse::<i8>() {
+                    Ok(score) => claims.push(CategoryClaim { category, score }),
+                    Err(_) => {
+                        println!("invalid number");
+                        continue 'input;
+                    }
+                }
+            }
+
+            return claims;
+        }
+    }
+
+    fn get_spoken_count(&mut self, _hand: &Hand<'_>) -> SpokenCount {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            io::stdin().read_line(&mut line).unwrap();
+            match spoken::parse(&line) {
+                Ok(count) => return count,
+                Err(e) => println!("{}. Count like \"fifteen two, fifteen four, and a pair is six\".", e),
+            }
+        }
+    }
+
+    fn display_component_result(&mut self, result: &ComponentResult) {
+        let ComponentResult { component, claimed, actual } = *result;
+        if claimed == actual {
+            println!("Correct! {}.", component.describe(actual));
+        } else if claimed > actual {
+            println!("Too many! You said {}, but there's only {}.", component.describe(claimed), actual);
+        } else {
+            println!("Too few! You said {}, but there's {}.", component.describe(claimed), actual);
+        }
+    }
+
+    fn display_wrong_total(&mut self, said: i32, actual: i32) {
+        println!("You said {}, but what you'd counted by then comes to {}.", said, actual);
+    }
+}
+
+fn print_explained_combos(hand: &Hand<'_>, combos: &[Combo<'_>]) {
+    for combo in combos {
+        println!("{}: {} points for a {} ({})",
+            combo_str(combo), combo.score, combo.kind, explain::explain_combo(combo, hand));
+    }
+}
+
+fn combo_str(combo: &Combo<'_>) -> String {
+    let cards: Vec<String> = combo.cards.iter().map(|card| card.to_string()).collect();
+    cards.join(" ")
 }
 
 fn parse_cards(input: &str) -> Result<Vec<Card>, CardParseError> {
@@ -164,15 +549,36 @@ fn parse_cards(input: &str) -> Result<Vec<Card>, CardParseError> {
     Ok(cards)
 }
 
-fn print_all_combos(input: &str) {
+/// The first card given more than once, if any is.
+fn find_duplicate(cards: &[Card]) -> Option<Card> {
+    cards.iter().enumerate()
+        .find(|(i, card)| cards[.. *i].contains(card))
+        .map(|(_, card)| *card)
+}
+
+fn print_all_combos(input: &str, settings: &Settings, crib: bool) {
     match parse_cards(input) {
         Ok(ref cards) => {
-            let hand = Hand::new(cards);
-            for combo in hand.find_all_combos() {
+            // A full show is the cards kept plus the starter.
+            let hand = if crib && cards.len() == settings.rules.crib_size() + 1 {
+                Hand::crib(cards)
+            } else if cards.len() == settings.rules.hand_size() + 1 {
+                Hand::with_starter(cards)
+            } else {
+                Hand::new(cards)
+            };
+            let mut combos = hand.find_all_combos();
+            if settings.pair_style == PairStyle::Individual {
+                combos = combo::split_pairs(combos);
+            }
+            if settings.compound_runs {
+                combos = combo::combine_compounds(combos);
+            }
+            for combo in combos {
                 for card in combo.cards {
                     print!("{} ", card);
                 }
-                println!("- {} for {} points", combo.text, combo.score);
+                println!("- {} for {} points", combo.kind, combo.score);
             }
         },
         Err(e) => {
@@ -181,34 +587,577 @@ fn print_all_combos(input: &str) {
     }
 }
 
-fn main() {
-    let args: Vec<String> = ::std::env::args().collect();
-    if args.len() > 1 {
-        if &args[1] == "-h" || &args[1] == "--help" {
-            println!("usage: {} [cards]
+fn print_starters(input: &str, settings: &Settings, crib: bool) {
+    let cards = match parse_cards(input) {
+        Ok(cards) => cards,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if let Some(card) = find_duplicate(&cards) {
+        println!("{} is in there twice", card);
+        return;
+    }
+    // The cards kept, and then maybe the ones thrown away, which can't be the starter.
+    let (kept, thrown) = if crib {
+        (settings.rules.crib_size(), 0)
+    } else {
+        (settings.rules.hand_size(), settings.rules.discard)
+    };
+    if cards.len() != kept && cards.len() != kept + thrown {
+        println!("--starters needs the {} cards kept, and then optionally the {} thrown away", kept, thrown);
+        return;
+    }
+    let (held, elsewhere) = cards.split_at(kept);
+
+    let scores = starters::starter_scores(held, elsewhere, crib);
+    let mut show = held.to_vec();
+    for score in &scores {
+        show.push(score.starter);
+        let hand = if crib { Hand::crib(&show) } else { Hand::with_starter(&show) };
+        let gained: Vec<String> = hand.find_all_combos().iter()
+            .filter(|combo| combo.kind == ComboKind::Nobs || combo.cards.contains(&&score.starter))
+            .map(|combo| format!("{} {}", combo.kind, combo_str(combo)))
+            .collect();
+        let line = format!("{:<4}{:>3}  {}", score.starter.to_string(), score.score, gained.join(", "));
+        println!("{}", line.trim_end());
+        show.pop();
+    }
+
+    let summary = starters::summarize(&scores);
+    println!("over {} starters:", scores.len());
+    println!("expected score: {:.2}", summary.mean);
+    println!("variance: {:.2} (standard deviation {:.2})", summary.variance, summary.variance.sqrt());
+    let mut thresholds: Vec<i32> = scores.iter().map(|x| x.score).collect();
+    thresholds.sort();
+    thresholds.dedup();
+    for points in thresholds {
+        println!("at least {:>2}: {:5.1}%", points, starters::chance_at_least(&scores, points) * 100.);
+    }
+}
+
+/// Turns on the score table, and says so if it couldn't be cached for next time.
+fn enable_table() -> &'static ScoreTable {
+    let (table, saved) = table::enable();
+    if let (Err(e), Some(path)) = (saved, table::cache_path()) {
+        println!("couldn't save the score table to {}: {}", path.display(), e);
+    }
+    table
+}
+
+fn print_distribution(distinct: bool) {
+    enable_table();
+    let distribution = analyze::score_distribution(distinct);
+    let total = distribution.total();
+    println!("{} hands:", total);
+    for (score, count) in distribution.counts.iter().enumerate() {
+        println!("{:>2}: {:>9} ({:.4}%)", score, count, *count as f64 * 100. / total as f64);
+    }
+    let impossible: Vec<String> = distribution.impossible().iter().map(|score| score.to_string()).collect();
+    println!("impossible scores: {}", impossible.join(", "));
+    println!("mean: {:.4}", distribution.mean());
+    println!("median: {}", distribution.median());
+}
+
+/// Deals hands matching the constraints given on the command line.
+fn print_generated(program: &str, args: &[String]) {
+    let usage = || {
+        println!("usage: {} generate [--seed <n>] [--count <n>] [--rules <rules>] [--crib]
+       [--score <n>|<low>-<high>] [--fifteens <n>] [--double-run] [--nobs] [--flush]", program);
+    };
+    let mut rules = Rules::default();
+    let mut seed = None;
+    let mut count = 1;
+    let mut crib = false;
+    let mut constraints = vec![];
+
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        let mut number = || arg_iter.next().and_then(|n| n.parse::<u64>().ok());
+        match arg.as_str() {
+            "--seed" => match number() {
+                Some(n) => seed = Some(n),
+                None => {
+                    usage();
+                    return;
+                }
+            },
+            "--count" => match number() {
+                Some(n) => count = n,
+                None => {
+                    usage();
+                    return;
+                }
+            },
+            "--rules" => {
+                match arg_iter.next().and_then(|name| Rules::from_name(name)) {
+                    Some(r) => rules = r,
+                    None => {
+                        println!("unknown rules");
+                        return;
+                    }
+                }
+            },
+            "--crib" => crib = true,
+            "--score" => {
+                let range: Option<Vec<i32>> = arg_iter.next()
+                    .and_then(|range| range.split('-').map(|n| n.parse().ok()).collect());
+                match range.as_deref() {
+                    Some([score]) => constraints.push(Constraint::Score(*score ..= *score)),
+                    Some([low, high]) => constraints.push(Constraint::Score(*low ..= *high)),
+                    _ => {
+                        println!("--score needs a score or a range, like 12-16");
+                        return;
+                    }
+                }
+            },
+            "--fifteens" => match number() {
+                Some(n) => constraints.push(Constraint::Fifteens(n as usize)),
+                None => {
+                    usage();
+                    return;
+                }
+            },
+            "--double-run" => constraints.push(Constraint::DoubleRun),
+            "--nobs" => constraints.push(Constraint::Nobs),
+            "--flush" => constraints.push(Constraint::Flush),
+            _ => {
+                usage();
+                return;
+            }
+        }
+    }
+
+    let mut generator = match seed {
+        Some(seed) => Generator::new(seed, rules),
+        None => Generator::from_entropy(rules),
+    };
+    println!("seed {}", generator.seed());
+    for _ in 0 .. count {
+        let cards = if crib { generator.crib(&constraints) } else { generator.hand(&constraints) };
+        match cards {
+            Some(ref cards) => {
+                let hand = if crib { Hand::crib(cards) } else { Hand::with_starter(cards) };
+                println!("{}  ({} points)", hand, hand.score());
+            },
+            None => {
+                println!("couldn't find a hand like that");
+                return;
+            }
+        }
+    }
+}
+
+fn print_discard_advice(program: &str, args: &[String]) {
+    let usage = || {
+        println!("usage: {} advise-discard <cards> --dealer|--pone [--rules <rules>] [--strategy <strategy>]",
+            program);
+        println!("    strategies: {}", discard::STRATEGY_NAMES.join(", "));
+    };
+    let mut rules = Rules::default();
+    let mut dealer = None;
+    let mut strategy_name = "crib-ev".to_owned();
+    let mut cards = String::new();
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--dealer" => dealer = Some(true),
+            "--pone" => dealer = Some(false),
+            "--rules" => match arg_iter.next().and_then(|name| Rules::from_name(name)) {
+                Some(r) => rules = r,
+                None => {
+                    usage();
+                    return;
+                }
+            },
+            "--strategy" => match arg_iter.next() {
+                Some(name) => strategy_name = name.clone(),
+                None => {
+                    usage();
+                    return;
+                }
+            },
+            _ => {
+                cards.push_str(arg);
+                cards.push(' ');
+            }
+        }
+    }
+
+    let dealer = match dealer {
+        Some(dealer) => dealer,
+        None => {
+            usage();
+            return;
+        }
+    };
+    let mut strategy = match discard::strategy_from_name(&strategy_name, &rules) {
+        Some(strategy) => strategy,
+        None => {
+            usage();
+            return;
+        }
+    };
+    let dealt = match parse_cards(&cards) {
+        Ok(ref dealt) if dealt.len() == rules.deal => dealt.clone(),
+        Ok(_) => {
+            println!("give the {} cards dealt", rules.deal);
+            return;
+        },
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    if let Some(card) = find_duplicate(&dealt) {
+        println!("{} is in there twice", card);
+        return;
+    }
+
+    enable_table();
+    let others = rules.crib_size() - rules.discard;
+    println!("{:<20}{:<10}{:>7}{:>7}{:>12}", "keep", "throw", "hand", "crib", strategy.name());
+    for option in discard::rank(&mut *strategy, &dealt, rules.discard, dealer) {
+        let kept: Vec<String> = option.kept.iter().map(Card::to_string).collect();
+        let thrown: Vec<String> = option.thrown.iter().map(Card::to_string).collect();
+        // Whatever the strategy didn't work out already.
+        let hand = option.value.hand.unwrap_or_else(|| discard::hand_ev(&option.kept, &option.thrown));
+        let crib = option.value.crib.unwrap_or_else(|| {
+            discard::crib_ev(&option.thrown, &option.kept, others) * if dealer { 1. } else { -1. }
+        });
+        println!("{:<20}{:<10}{:>7.2}{:>+7.2}{:>12.2}", kept.join(" "), thrown.join(" "), hand, crib,
+            option.value.total);
+    }
+}
+
+fn play_lessons(program: &str, args: &[String]) {
+    let mut player = std::env::var("USER").unwrap_or_else(|_| "player".to_owned());
+    let mut chosen = None;
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--player" => match arg_iter.next() {
+                Some(name) => player = name.clone(),
+                None => {
+                    println!("--player needs a name");
+                    return;
+                }
+            },
+            name => match lessons::find_unit(name) {
+                Some(unit) => chosen = Some(unit),
+                None => {
+                    println!("usage: {} lessons [--player <name>] [unit]", program);
+                    return;
+                }
+            },
+        }
+    }
+
+    let path = lessons::progress_path(&player);
+    let mut progress = path.as_ref()
+        .and_then(|path| Progress::load(path).ok())
+        .unwrap_or_default();
+    for unit in &lessons::UNITS {
+        let status = if progress.passed.contains(unit.name) {
+            "passed"
+        } else if progress.unlocked(unit) {
+            "open"
+        } else {
+            "locked"
+        };
+        println!("  {:<14} {:<26} {}", unit.name, unit.title, status);
+    }
+
+    let mut unit = match chosen.or_else(|| progress.next_unit()) {
+        Some(unit) => unit,
+        None => {
+            println!("You've passed every unit! Pick one to go over it again.");
+            return;
+        }
+    };
+    if !progress.unlocked(unit) {
+        println!("Pass the units before {} first.", unit.name);
+        return;
+    }
+
+    let ui = RefCell::new(ConsoleUI {
+        player_score: 0,
+        cpu_score: 0,
+        lowball: false,
+    });
+    let mut game = Game::new(&ui, Settings::default());
+    let mut generator = Generator::from_entropy(Rules::STANDARD);
+    loop {
+        println!();
+        let perfect = game.play_lesson(unit, &mut generator);
+        let passed = perfect >= unit.pass;
+        if passed {
+            progress.pass(unit);
+            if let Some(ref path) = path {
+                if let Err(e) = progress.save(path) {
+                    eprintln!("couldn't save your progress to {:?}: {}", path, e);
+                }
+            }
+        }
+
+        let next = if passed {
+            lessons::UNITS.iter().skip_while(|x| x.name != unit.name).nth(1)
+        } else {
+            Some(unit)
+        };
+        let next = match next {
+            Some(next) => next,
+            None => {
+                println!("You passed! That's the whole course.");
+                return;
+            }
+        };
+        if passed {
+            print!("You passed! Go on to {}? [y/n] ", next.title);
+        } else {
+            print!("You need {} to pass. Try again? [y/n] ", unit.pass);
+        }
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).unwrap();
+        if !line.to_lowercase().starts_with('y') {
+            break;
+        }
+        unit = next;
+    }
+}
+
+fn print_usage(program: &str) {
+    println!("usage: {0} [options] [cards]
+       {0} analyze distribution [--distinct]|table
+       {0} generate [options]
+       {0} lessons [--player <name>] [unit]
+       {0} advise-discard <cards> --dealer|--pone [options]
     With no arguments, play a game.
     Or, give a set of cards, and the program will list all valid combos for
         those cards.
     Cards are given as the value, as the number of the card or A, J, Q, K (for
         ace, jack, queen, king); and the suit H, D, S, C (for hearts, diamonds,
-        spades, clubs).", args[0]);
-        } else {
-            let mut cards = String::new();
-            for arg in args.iter().skip(1) {
+        spades, clubs). If a full show is given (five cards, unless the rules
+        say otherwise), the last one is the starter.
+    While playing, type \"hint\" if you're stuck. Each hint tells you a bit
+        more about what's left to count, and costs you points. Type \"undo\" to
+        take back your last guess and any points it won, or the penalty for
+        one that wasn't a combo at all. A wrong score stays penalised.
+options:
+    --rules <rules>: which variant to play: standard (the default),
+        five-card (keep three, play to 61), seven-card (keep five),
+        three-player, or four-player. Every other hand, you deal, and count
+        the crib as well as your hand.
+    --lowball: the lowest score wins. The computer counts its hand too, and
+        anything you miss is pegged against you.
+    --cross: cross-cribbage. Count every row and column of a 5x5 grid, each
+        a hand of its own with no starter, and then the crib.
+    --catch: the computer counts its own hand, and sometimes gets it wrong.
+        Accept its count, or call muggins on the combos it missed or counted
+        that aren't there.
+    --best-starter: look at the cards you kept, and say which starter would
+        score the most, or which starters could give at least some number of
+        points. Name card numbers, like \"5\" or \"4 6 J\".
+    --crib: when listing combos, count the cards as a crib, where a flush
+        has to include the starter.
+    --starters: give the cards kept (and, if you like, the ones thrown away),
+        and see what every possible starter would score, what it adds, and the
+        expected score, its variance, and the chances of reaching each score.
+        With --crib, give the four cards in the crib.
+    --by-category: instead of listing the cards of each combo, announce a
+        total per category, like \"fifteens 6, pairs 2, runs 3\".
+    --spoken: count the way you would out loud, like \"fifteen two, fifteen
+        four, and a pair is six\" or \"fifteen six and a double run is
+        fourteen\". Each kind of combo is checked, and so are the totals.
+    --multiple-choice: for beginners. Instead of counting the hand yourself,
+        pick its total out of four, or pick the one combo out of four that
+        doesn't really score.
+    --split-pairs: show three or four of a kind as their individual pairs.
+        Either way, you can claim them as a group or pair by pair.
+    --compound-runs: show runs together with their pairs as double runs,
+        triple runs, and double double runs. Either way, you can claim them
+        together or one at a time.
+    --adaptive: deal more hands with the kinds of combos you tend to miss,
+        and pick them harder as you get better. What you miss is kept from
+        one session to the next either way.
+    --drill <category>: only count one category of combo, like fifteens or
+        runs, and get dealt hands with plenty of them.
+    --cpu-discard <strategy>: how the computer picks the cards it throws
+        into the crib, as with advise-discard. Normally it throws the first
+        ones it's dealt.
+    --muggins-skill <percent>: how often the computer notices a combo you
+        missed (or, counting by category, a category you came up short in)
+        and takes it as muggins. Normally it never misses one.
+    --hint-costs <costs>: what each hint in a hand costs, like 1,1,2 (the
+        default). The last one is the cost of any more after that.
+    analyze distribution: score every possible hand and starter, and show how
+        often each score comes up. With --distinct, only score one show out of
+        each group that differ just by which suit is which.
+    generate: deal hands with particular features. Give --score with a score
+        or a range like 12-16, --fifteens with the least number of fifteens,
+        --double-run, --nobs, or --flush, in any combination; --crib to deal
+        cribs instead of hands; and --count for how many. It prints a seed,
+        which you can give back with --seed to get the same hands again.
+    advise-discard: give the cards dealt, and whether the crib is yours
+        (--dealer) or not (--pone), and see every way to discard, best first.
+        Each shows what the hand is expected to score over every starter, and
+        what the crib is expected to score, for you or against you. Rank them
+        with --strategy: crib-ev (the default) counts both; hand-ev just the
+        hand; guaranteed only what the hand has before the starter; and
+        random, for comparison. --rules works here too.
+    lessons: a course for new players, one kind of combo at a time, from
+        fifteens up to counting whole hands. Count enough of a unit's hands
+        perfectly and the next one opens. Progress is kept for each --player
+        (your user name by default); name a unit to go over it again.
+    analyze table: build the table of scores for every show, and cache it on
+        disk so that tools that score lots of hands can look them up.", program);
+}
+
+fn main() {
+    let args: Vec<String> = ::std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("analyze") {
+        match args.get(2).map(String::as_str) {
+            Some("distribution") => {
+                print_distribution(args.get(3).map(String::as_str) == Some("--distinct"));
+            },
+            Some("table") => {
+                let (table, saved) = table::enable();
+                println!("{} shows", table.len());
+                match (table::cache_path(), saved) {
+                    (Some(path), Ok(())) => println!("cached at {}", path.display()),
+                    (Some(path), Err(e)) => println!("couldn't save it to {}: {}", path.display(), e),
+                    (None, _) => println!("not cached: no home directory"),
+                }
+            },
+            _ => println!("usage: {} analyze distribution [--distinct]|table", args[0]),
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("generate") {
+        print_generated(&args[0], &args[2 ..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("advise-discard") {
+        print_discard_advice(&args[0], &args[2 ..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("lessons") {
+        play_lessons(&args[0], &args[2 ..]);
+        return;
+    }
+
+    let mut settings = Settings::default();
+    let mut crib = false;
+    let mut starters = false;
+    let mut cards = String::new();
+    let mut arg_iter = args.iter().skip(1);
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_usage(&args[0]);
+                return;
+            },
+            "--rules" => {
+                match arg_iter.next().and_then(|name| Rules::from_name(name)) {
+                    Some(rules) => settings.rules = rules,
+                    None => {
+                        let names: Vec<&str> = Rules::ALL.iter().map(|rules| rules.name).collect();
+                        println!("--rules needs one of: {}", names.join(", "));
+                        return;
+                    }
+                }
+            },
+            "--crib" => crib = true,
+            "--starters" => starters = true,
+            "--lowball" => settings.mode = Mode::Lowball,
+            "--cross" => settings.mode = Mode::CrossCribbage,
+            "--catch" => settings.mode = Mode::CatchTheComputer,
+            "--best-starter" => settings.mode = Mode::BestStarter,
+            "--adaptive" => settings.adaptive = true,
+            "--by-category" => settings.guess_style = GuessStyle::Categories,
+            "--spoken" => settings.guess_style = GuessStyle::Spoken,
+            "--multiple-choice" => settings.guess_style = GuessStyle::MultipleChoice,
+            "--split-pairs" => settings.pair_style = PairStyle::Individual,
+            "--compound-runs" => settings.compound_runs = true,
+            "--drill" => {
+                match arg_iter.next().and_then(|name| Category::from_name(name)) {
+                    Some(category) => settings.drill = Some(category),
+                    None => {
+                        println!("--drill needs a category: fifteens, pairs, runs, flush, or nobs");
+                        return;
+                    }
+                }
+            },
+            "--cpu-discard" => {
+                match arg_iter.next().and_then(|name| discard::STRATEGY_NAMES.iter().find(|x| *x == name)) {
+                    Some(name) => settings.cpu_discard = Some(name),
+                    None => {
+                        println!("--cpu-discard needs a strategy: {}", discard::STRATEGY_NAMES.join(", "));
+                        return;
+                    }
+                }
+            },
+            "--muggins-skill" => {
+                match arg_iter.next().and_then(|percent| percent.parse::<u8>().ok()) {
+                    Some(percent) if percent <= 100 => settings.muggins_skill = f64::from(percent) / 100.,
+                    _ => {
+                        println!("--muggins-skill needs a percentage, from 0 to 100");
+                        return;
+                    }
+                }
+            },
+            "--hint-costs" => {
+                let costs: Result<Vec<i32>, _> = arg_iter.next()
+                    .map(|costs| costs.split(',').map(str::parse).collect())
+                    .unwrap_or_else(|| Ok(vec![]));
+                match costs {
+                    Ok(ref costs) if !costs.is_empty() => settings.hint_costs = costs.clone(),
+                    _ => {
+                        println!("--hint-costs needs a list of numbers, like 1,1,2");
+                        return;
+                    }
+                }
+            },
+            _ => {
                 cards.push_str(arg);
-                cards.push_str(" ");
+                cards.push(' ');
             }
-            print_all_combos(&cards);
         }
+    }
+
+    if starters {
+        print_starters(&cards, &settings, crib);
+        return;
+    }
+    if !cards.is_empty() {
+        print_all_combos(&cards, &settings, crib);
         return;
     }
 
     let ui = RefCell::new(ConsoleUI {
         player_score: 0,
-        cpu_score: 0
+        cpu_score: 0,
+        lowball: settings.mode == Mode::Lowball,
     });
 
-    let mut game = Game::new(&ui);
+    if let Some(category) = settings.drill {
+        println!("Drilling {}: nothing else counts.", category);
+    }
+    let adaptive = settings.adaptive;
+    let mut game = Game::new(&ui, settings);
+    let stats_path = stats::stats_path();
+    if let Some(stats) = stats_path.as_ref().and_then(|path| Stats::load(path).ok()) {
+        if adaptive {
+            if let Some(category) = stats.weakest() {
+                println!("You miss {} the most ({:.0}% of the time), so watch out for them.",
+                    category, stats.miss_rate(category) * 100.);
+            }
+        }
+        game.set_stats(stats);
+    }
 
     let mut deck = Deck::new();
     loop {
@@ -216,10 +1165,37 @@ fn main() {
 
         deck.shuffle();
         game.play(&deck);
+        if let Some(ref path) = stats_path {
+            if let Err(e) = game.stats().save(path) {
+                eprintln!("couldn't save your stats to {:?}: {}", path, e);
+            }
+        }
 
-        let ui = ui.borrow();
+        let mut ui = ui.borrow_mut();
         println!("Score total: You: {}", ui.player_score);
         println!("        Computer: {}", ui.cpu_score);
+        let (right, asked) = game.accuracy();
+        if asked > 0 {
+            println!("Right answers: {} of {} ({:.0}%)", right, asked, right as f64 * 100. / asked as f64);
+        }
+        let game_length = game.settings().rules.game_length;
+        let lowball = game.settings().mode == Mode::Lowball;
+        if ui.player_score >= game_length || ui.cpu_score >= game_length {
+            if lowball {
+                if ui.player_score >= ui.cpu_score {
+                    println!("You reached {} first. The computer wins the game.", game_length);
+                } else {
+                    println!("The computer reached {} first. You win the game!", game_length);
+                }
+            } else if ui.player_score >= ui.cpu_score {
+                println!("You reached {} first. You win the game!", game_length);
+            } else {
+                println!("The computer reached {} first, and wins the game.", game_length);
+            }
+            ui.player_score = 0;
+            ui.cpu_score = 0;
+            game.new_game();
+        }
         print!("Play again? [y/n] ");
         io::stdout().flush().unwrap();
 
@@ -0,0 +1,219 @@
+// Packed :: a compact representation of a set of cards, and a fast scorer that works on it.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// `Hand::find_all_combos` builds a list of every combo, which is what you want for showing a
+// player what they missed, but is far too slow when all you need is the total for millions of
+// hands. The scorer here doesn't allocate at all.
+//
+
+use crate::card::{self, Card, Suit};
+
+/// A set of cards, one bit per card, at the position given by `Card::index`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct CardSet(pub u64);
+
+/// The bits for every card of the first suit; shift left by the suit number for the others.
+const SUIT_MASK: u64 = 0x1_1111_1111_1111;
+
+impl CardSet {
+    pub fn from_cards(cards: &[Card]) -> CardSet {
+        let mut set = CardSet::default();
+        for card in cards {
+            set.insert(card);
+        }
+        set
+    }
+
+    pub fn insert(&mut self, card: &Card) {
+        self.0 |= 1 << card.index();
+    }
+
+    pub fn contains(self, card: &Card) -> bool {
+        self.0 & (1 << card.index()) != 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The cards in the set, in deck order.
+    pub fn to_cards(self) -> Vec<Card> {
+        (0 .. 52).filter(|i| self.0 & (1 << i) != 0).map(Card::from_index).collect()
+    }
+
+    /// How many cards of each number there are, indexed by number - 1.
+    pub fn number_counts(self) -> [u8; 13] {
+        let mut counts = [0; 13];
+        for (number, count) in counts.iter_mut().enumerate() {
+            *count = ((self.0 >> (number * 4)) & 0xF).count_ones() as u8;
+        }
+        counts
+    }
+
+    /// The numbers of the cards of one suit, as a bit for each number: bit 0 is the ace.
+    pub fn suit_numbers(self, suit: Suit) -> u16 {
+        let mut numbers = 0;
+        for number in 0 .. 13 {
+            numbers |= (((self.0 >> (number * 4 + suit as usize)) & 1) as u16) << number;
+        }
+        numbers
+    }
+
+    /// The same cards, with the suits swapped around: cards of suit `s` become suit `to[s]`.
+    pub fn relabel(self, to: &[Suit; 4]) -> CardSet {
+        let mut set = 0;
+        for suit in &Suit::ALL {
+            set |= ((self.0 >> *suit as usize) & SUIT_MASK) << to[*suit as usize] as usize;
+        }
+        CardSet(set)
+    }
+}
+
+/// Relabels the suits of a show so that every show which differs only by which suit is which comes
+/// out the same. Swapping suits around doesn't change the score, as long as it's done to all the
+/// cards including the starter.
+pub fn canonical(held: CardSet, starter: &Card) -> (CardSet, Card) {
+    let to = canonical_suits(held, starter);
+    let starter = Card { number: starter.number, suit: to[starter.suit as usize] };
+    (held.relabel(&to), starter)
+}
+
+/// How many different shows are the same as this one apart from which suit is which.
+pub fn multiplicity(held: CardSet, starter: &Card) -> u32 {
+    card::suit_multiplicity(|suit| suit_signature(held, Some(starter), suit))
+}
+
+fn canonical_suits(held: CardSet, starter: &Card) -> [Suit; 4] {
+    card::canonical_suits(|suit| suit_signature(held, Some(starter), suit))
+}
+
+/// What distinguishes a suit from the others: the numbers held in it, and then whether it's the
+/// starter's suit.
+pub fn suit_signature(held: CardSet, starter: Option<&Card>, suit: Suit) -> u32 {
+    (u32::from(held.suit_numbers(suit)) << 1) | starter.is_some_and(|card| card.suit == suit) as u32
+}
+
+/// Scores four held cards plus the starter, without building any combos.
+pub fn score(held: CardSet, starter: &Card) -> u8 {
+    let mut all = held;
+    all.insert(starter);
+    let counts = all.number_counts();
+
+    score_fifteens(&counts) + score_pairs(&counts) + score_runs(&counts)
+        + score_flush(held, starter) + score_nobs(held, starter)
+}
+
+fn score_fifteens(counts: &[u8; 13]) -> u8 {
+    // ways[n] is how many subsets of the cards seen so far add up to n.
+    let mut ways = [0u8; 16];
+    ways[0] = 1;
+    for (number, &count) in counts.iter().enumerate() {
+        let value = (number + 1).min(10);
+        for _ in 0 .. count {
+            for total in (value ..= 15).rev() {
+                ways[total] += ways[total - value];
+            }
+        }
+    }
+    2 * ways[15]
+}
+
+fn score_pairs(counts: &[u8; 13]) -> u8 {
+    // n of a kind makes n take 2 pairs, worth 2 each.
+    counts.iter().map(|&n| n * n.saturating_sub(1)).sum()
+}
+
+fn score_runs(counts: &[u8; 13]) -> u8 {
+    let mut score = 0;
+    let mut length = 0;
+    let mut multiplier = 1;
+    for &count in counts.iter().chain(Some(&0)) {
+        if count == 0 {
+            if length >= 3 {
+                score += length * multiplier;
+            }
+            length = 0;
+            multiplier = 1;
+        } else {
+            length += 1;
+            multiplier *= count;
+        }
+    }
+    score
+}
+
+fn score_flush(held: CardSet, starter: &Card) -> u8 {
+    let suit_mask = SUIT_MASK << starter.suit as usize;
+    for suit in 0 .. 4 {
+        let mask = SUIT_MASK << suit;
+        if held.0 & !mask == 0 {
+            return if mask == suit_mask { 5 } else { 4 };
+        }
+    }
+    0
+}
+
+fn score_nobs(held: CardSet, starter: &Card) -> u8 {
+    let jack = Card { number: 11, suit: starter.suit };
+    if held.contains(&jack) { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::hand::Hand;
+
+    fn show() -> impl Strategy<Value = Vec<Card>> {
+        proptest::sample::subsequence((0 .. 52).collect::<Vec<usize>>(), 5)
+            .prop_shuffle()
+            .prop_map(|indices| indices.into_iter().map(Card::from_index).collect())
+    }
+
+    proptest! {
+        #[test]
+        fn test_score_matches_itemized(cards in show()) {
+            let held = CardSet::from_cards(&cards[.. 4]);
+            let expected = Hand::with_starter(&cards).score();
+            prop_assert_eq!(i32::from(score(held, &cards[4])), expected);
+        }
+
+        #[test]
+        fn test_canonical_ignores_suits(
+            cards in show(),
+            to in Just(Suit::ALL.to_vec()).prop_shuffle(),
+        ) {
+            let held = CardSet::from_cards(&cards[.. 4]);
+            let to = [to[0], to[1], to[2], to[3]];
+            let starter = Card { number: cards[4].number, suit: to[cards[4].suit as usize] };
+            let (canonical_held, canonical_starter) = canonical(held, &cards[4]);
+            prop_assert_eq!(canonical(held.relabel(&to), &starter), (canonical_held, canonical_starter));
+            prop_assert_eq!(score(canonical_held, &canonical_starter), score(held, &cards[4]));
+        }
+    }
+}
+
+#[test]
+fn test_score() {
+    use crate::card::parse_cards;
+    use std::str::FromStr;
+
+    let score_str = |held: &str, starter: &str| -> u8 {
+        let cards = parse_cards(held);
+        score(CardSet::from_cards(&cards), &Card::from_str(starter).unwrap())
+    };
+    assert_eq!(score_str("5h 5c 5s jd", "5d"), 29);
+    assert_eq!(score_str("3s 4s 5s 4h", "5d"), 16);
+    assert_eq!(score_str("2h 4h 6h 8h", "qh"), 5);
+    assert_eq!(score_str("2h 4h 6h 8h", "qs"), 4);
+    assert_eq!(score_str("2h 4h 6h 8s", "qh"), 0);
+    assert_eq!(score_str("ah 2c 3d 4s", "5h"), 7);
+    assert_eq!(score_str("jh 2c 8d 4s", "qh"), 1);
+    assert_eq!(score_str("kh qc 9d 7s", "2h"), 0);
+}
@@ -0,0 +1,135 @@
+// Rules :: the variants of cribbage, and how they differ in dealing and scoring.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use std::fmt::{self, Display, Formatter};
+
+/// How many cards each player is dealt and keeps, how the crib is made up, and how long the game
+/// is. Every variant scores the show the same way, with a starter and a four-card crib; the only
+/// difference in scoring is how many cards the hands have.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rules {
+    pub name: &'static str,
+    pub players: usize,
+    /// Cards dealt to each player.
+    pub deal: usize,
+    /// Cards each player puts in the crib.
+    pub discard: usize,
+    /// Cards dealt straight from the deck into the crib, to make it up to four.
+    pub crib_from_deck: usize,
+    /// Points needed to win the game.
+    pub game_length: i32,
+    /// Points the player who doesn't deal first gets at the start of the game. In five-card
+    /// cribbage, the crib is bigger than either hand, so the dealer has enough of an advantage
+    /// that the other player gets three points to make up for it.
+    pub pone_bonus: i32,
+}
+
+impl Rules {
+    /// Modern two-player cribbage: deal six, keep four.
+    pub const STANDARD: Rules = Rules {
+        name: "standard",
+        players: 2,
+        deal: 6,
+        discard: 2,
+        crib_from_deck: 0,
+        game_length: 121,
+        pone_bonus: 0,
+    };
+
+    /// The original game: deal five, keep three, play to 61.
+    pub const FIVE_CARD: Rules = Rules {
+        name: "five-card",
+        players: 2,
+        deal: 5,
+        discard: 2,
+        crib_from_deck: 0,
+        game_length: 61,
+        pone_bonus: 3,
+    };
+
+    /// Deal seven, keep five.
+    pub const SEVEN_CARD: Rules = Rules {
+        name: "seven-card",
+        players: 2,
+        deal: 7,
+        discard: 2,
+        crib_from_deck: 0,
+        game_length: 121,
+        pone_bonus: 0,
+    };
+
+    /// Deal five each, and one more to the crib; each player discards one.
+    pub const THREE_PLAYER: Rules = Rules {
+        name: "three-player",
+        players: 3,
+        deal: 5,
+        discard: 1,
+        crib_from_deck: 1,
+        game_length: 121,
+        pone_bonus: 0,
+    };
+
+    /// Deal five each; each player discards one.
+    pub const FOUR_PLAYER: Rules = Rules {
+        name: "four-player",
+        players: 4,
+        deal: 5,
+        discard: 1,
+        crib_from_deck: 0,
+        game_length: 121,
+        pone_bonus: 0,
+    };
+
+    pub const ALL: [Rules; 5] = [
+        Rules::STANDARD,
+        Rules::FIVE_CARD,
+        Rules::SEVEN_CARD,
+        Rules::THREE_PLAYER,
+        Rules::FOUR_PLAYER,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Rules> {
+        Rules::ALL.iter().find(|rules| rules.name == name).cloned()
+    }
+
+    /// How many cards each player keeps for their show, not counting the starter.
+    pub fn hand_size(&self) -> usize {
+        self.deal - self.discard
+    }
+
+    pub fn crib_size(&self) -> usize {
+        self.players * self.discard + self.crib_from_deck
+    }
+
+    /// How many cards a round uses, including the starter.
+    pub fn cards_used(&self) -> usize {
+        self.players * self.deal + self.crib_from_deck + 1
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::STANDARD
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.name)
+    }
+}
+
+#[test]
+fn test_rules() {
+    for rules in &Rules::ALL {
+        assert_eq!(rules.crib_size(), 4, "{}", rules);
+        assert!(rules.cards_used() <= 52);
+        assert_eq!(Rules::from_name(rules.name), Some(*rules));
+    }
+    assert_eq!(Rules::FIVE_CARD.hand_size(), 3);
+    assert_eq!(Rules::SEVEN_CARD.hand_size(), 5);
+    assert_eq!(Rules::THREE_PLAYER.hand_size(), 4);
+    assert_eq!(Rules::from_name("six-card"), None);
+}
@@ -0,0 +1,432 @@
+// Spoken :: reads a count the way it's said out loud at the table, like "fifteen two, fifteen four,
+// and a pair is six".
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// Nobody names the cards when they count out loud, so what's said only tells how many of each kind
+// of combo there are, and the running total. Where the size of a run or a flush isn't said, the next
+// total says it: "fifteen two and a run is five" can only be a run of three.
+//
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
+
+use crate::combo::{Combo, ComboKind};
+
+/// One kind of combo, as far as a spoken count can tell them apart.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Component {
+    Fifteens,
+    /// Pairs, counted one by one: a pair royal is three.
+    Pairs,
+    /// Runs of the given length.
+    Runs(usize),
+    /// A flush of the given number of cards.
+    Flush(usize),
+    Nobs,
+}
+
+impl Component {
+    /// What each one is worth.
+    pub fn points(self) -> i32 {
+        match self {
+            Component::Fifteens | Component::Pairs => 2,
+            Component::Runs(n) | Component::Flush(n) => n as i32,
+            Component::Nobs => 1,
+        }
+    }
+
+    /// How many of it there are, in words, like "1 pair" or "2 runs of 3".
+    pub fn describe(self, n: usize) -> String {
+        let name = match (self, n) {
+            (Component::Fifteens, 1) => "fifteen".to_owned(),
+            (Component::Pairs, 1) => "pair".to_owned(),
+            (Component::Runs(length), 1) => format!("run of {}", length),
+            (Component::Flush(size), 1) => format!("{}-flush", size),
+            (Component::Nobs, _) => return if n == 0 { "no nobs".to_owned() } else { "nobs".to_owned() },
+            _ => self.to_string(),
+        };
+        format!("{} {}", n, name)
+    }
+
+    /// Which component a combo counts toward, or `None` for a compound, which counts toward several.
+    pub fn of(combo: &Combo<'_>) -> Option<Component> {
+        match combo.kind {
+            ComboKind::Fifteen => Some(Component::Fifteens),
+            ComboKind::Pair | ComboKind::PairRoyal | ComboKind::DoublePairRoyal => Some(Component::Pairs),
+            ComboKind::Run(n) => Some(Component::Runs(n)),
+            ComboKind::Flush(n) => Some(Component::Flush(n)),
+            ComboKind::Nobs | ComboKind::Heels => Some(Component::Nobs),
+            ComboKind::DoubleRun(_) | ComboKind::TripleRun(_) | ComboKind::DoubleDoubleRun(_) => None,
+        }
+    }
+}
+
+impl Display for Component {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Component::Fifteens => fmt.write_str("fifteens"),
+            Component::Pairs    => fmt.write_str("pairs"),
+            Component::Runs(n)  => write!(fmt, "runs of {}", n),
+            Component::Flush(n) => write!(fmt, "{}-flushes", n),
+            Component::Nobs     => fmt.write_str("nobs"),
+        }
+    }
+}
+
+/// What a spoken count claims.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SpokenCount {
+    /// How many of each component.
+    pub claims: BTreeMap<Component, usize>,
+    /// Each total said along the way, and what the claims up to there really add up to.
+    pub totals: Vec<(i32, i32)>,
+}
+
+impl SpokenCount {
+    /// What the claims add up to.
+    pub fn total(&self) -> i32 {
+        self.claims.iter().map(|(component, n)| component.points() * *n as i32).sum()
+    }
+
+    fn claim(&mut self, component: Component, n: usize) {
+        *self.claims.entry(component).or_insert(0) += n;
+    }
+}
+
+/// How one component of a spoken count compares with the hand.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentResult {
+    pub component: Component,
+    pub claimed: usize,
+    pub actual: usize,
+}
+
+/// Compares each component of a spoken count with the combos really there. Components that were
+/// neither claimed nor there are left out.
+pub fn check(count: &SpokenCount, combos: &[Combo<'_>]) -> Vec<ComponentResult> {
+    let mut actual: BTreeMap<Component, usize> = BTreeMap::new();
+    for combo in combos {
+        if let Some(component) = Component::of(combo) {
+            *actual.entry(component).or_insert(0) += combo.score as usize / component.points() as usize;
+        }
+    }
+    let mut components: Vec<Component> = actual.keys().chain(count.claims.keys()).cloned().collect();
+    components.sort();
+    components.dedup();
+    components.into_iter()
+        .map(|component| ComponentResult {
+            component,
+            claimed: count.claims.get(&component).cloned().unwrap_or(0),
+            actual: actual.get(&component).cloned().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// The combos of the component that a count came up short on. Which ones were missed can't be
+/// told from a count, so it's the last few; n-of-a-kinds are broken into pairs to count them.
+pub fn shortfall<'a>(result: &ComponentResult, combos: &[Combo<'a>]) -> Vec<Combo<'a>> {
+    combos.iter()
+        .filter(|combo| Component::of(combo) == Some(result.component))
+        .flat_map(Combo::pairs)
+        .skip(result.claimed)
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(i32),
+    Word(String),
+}
+
+const FILLER: &[&str] = &[
+    "a", "an", "and", "another", "is", "makes", "make", "equals", "that's", "thats", "the",
+    "his", "her", "plus", "with", "gives",
+];
+
+const NUMBERS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+    "twenty",
+];
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let text = text.to_lowercase();
+    let mut tokens: Vec<Token> = vec![];
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '\'')) {
+        if word.is_empty() || FILLER.contains(&word) {
+            continue;
+        }
+        let number = match word.parse::<i32>() {
+            Ok(n) => Some(n),
+            // Sorted out below, once it's known what comes after it.
+            Err(_) if word == "fifteen" => None,
+            Err(_) => NUMBERS.iter().position(|x| *x == word).map(|n| n as i32),
+        };
+        match (number, tokens.last_mut()) {
+            // "twenty four"
+            (Some(n), Some(Token::Number(20))) if (1 .. 10).contains(&n) => {
+                tokens.pop();
+                tokens.push(Token::Number(20 + n));
+            },
+            (Some(n), _) => tokens.push(Token::Number(n)),
+            (None, _) => tokens.push(Token::Word(word.to_owned())),
+        }
+    }
+
+    // "Fifteen" (or "15") followed by a total, or after "for", is the combo; otherwise it's a total
+    // itself, as in "and a flush is fifteen".
+    let fifteen = Token::Word("fifteen".to_owned());
+    for i in 0 .. tokens.len() {
+        let combo = matches!(tokens.get(i + 1), Some(Token::Number(_)))
+            || (i > 0 && tokens[i - 1] == Token::Word("for".to_owned()));
+        if tokens[i] == fifteen && !combo {
+            tokens[i] = Token::Number(15);
+        } else if tokens[i] == Token::Number(15) && combo {
+            tokens[i] = fifteen.clone();
+        }
+    }
+    tokens
+}
+
+/// The size of a run or flush: the one said, or else the one that makes up the points gained by
+/// the next total, or else the smallest.
+fn infer_size(said: Option<usize>, sizes: RangeInclusive<usize>, points: impl Fn(usize) -> i32,
+        gained: Option<i32>) -> usize {
+    let smallest = *sizes.start();
+    said.or_else(|| gained.and_then(|gained| sizes.into_iter().find(|&n| points(n) == gained)))
+        .unwrap_or(smallest)
+}
+
+/// Parses a spoken count. Errors say which word didn't make sense.
+pub fn parse(text: &str) -> Result<SpokenCount, String> {
+    let tokens = tokenize(text);
+    let mut count = SpokenCount::default();
+    let mut i = 0;
+    let word = |i: usize| match tokens.get(i) {
+        Some(Token::Word(w)) => Some(w.as_str()),
+        _ => None,
+    };
+    let number = |i: usize| match tokens.get(i) {
+        Some(Token::Number(n)) => Some(*n),
+        _ => None,
+    };
+    // The points between the total so far and the total said after a component, if one is.
+    let gained = |count: &SpokenCount, i: usize| number(i).map(|n| n - count.total());
+    let mut last_fifteen = false;
+
+    while i < tokens.len() {
+        // A number in front of a plural is how many: "two pairs".
+        let mut times = 1;
+        if let (Some(n), Some(next)) = (number(i), word(i + 1)) {
+            if n > 0 && ["pairs", "runs", "fifteens"].contains(&next) {
+                times = n as usize;
+                i += 1;
+            }
+        }
+        // "four for the flush": the points for what comes next, rather than a total.
+        let mut points_for = None;
+        if let (Some(n), Some("for")) = (number(i), word(i + 1)) {
+            points_for = Some(n);
+            i += 2;
+        }
+        // "one nobs"
+        if number(i) == Some(1) && ["nobs", "nob", "knobs", "knob"].contains(&word(i + 1).unwrap_or("")) {
+            i += 1;
+        }
+        let gained = |count: &SpokenCount, i: usize| points_for.or_else(|| gained(count, i));
+        // "four card flush"
+        if let (Some(n), Some("card"), Some("flush")) = (number(i), word(i + 1), word(i + 2)) {
+            count.claim(Component::Flush(n as usize), 1);
+            i += 3;
+            continue;
+        }
+
+        let was_fifteen = last_fifteen;
+        last_fifteen = false;
+        match tokens[i] {
+            Token::Number(n) => {
+                // "fifteen two, four, six": a bare number two more than a fifteen is another one.
+                if was_fifteen && n == count.total() + 2 {
+                    count.claim(Component::Fifteens, 1);
+                }
+                last_fifteen = was_fifteen;
+                count.totals.push((n, count.total()));
+                i += 1;
+                continue;
+            },
+            Token::Word(ref w) => match w.as_str() {
+                "fifteen" | "fifteens" => {
+                    // "fifteen six" on its own is three fifteens.
+                    let n = match gained(&count, i + 1) {
+                        Some(gained) if times == 1 && gained > 0 && gained % 2 == 0 => gained as usize / 2,
+                        _ => times,
+                    };
+                    count.claim(Component::Fifteens, n);
+                    last_fifteen = true;
+                    i += 1;
+                },
+                "pair" | "pairs" => {
+                    let royal = word(i + 1) == Some("royal");
+                    count.claim(Component::Pairs, times * if royal { 3 } else { 1 });
+                    i += if royal { 2 } else { 1 };
+                },
+                "run" | "runs" | "double" | "triple" | "quadruple" => {
+                    // How many runs, and how many pairs come with them.
+                    let mut j = i;
+                    let (runs, pairs) = match (w.as_str(), word(i + 1), word(i + 2)) {
+                        ("double", Some("double"), Some("run")) => { j += 2; (4, 2) },
+                        ("double", Some("pair"), Some("royal")) => {
+                            count.claim(Component::Pairs, times * 6);
+                            i += 3;
+                            continue;
+                        },
+                        ("double", Some("run"), _) => { j += 1; (2, 1) },
+                        ("triple", Some("run"), _) => { j += 1; (3, 3) },
+                        ("quadruple", Some("run"), _) => { j += 1; (4, 2) },
+                        ("run", _, _) | ("runs", _, _) => (1, 0),
+                        _ => return Err(format!("{} what?", w)),
+                    };
+                    j += 1;
+                    let said = match (word(j), number(j + 1)) {
+                        (Some("of"), Some(n)) => {
+                            j += 2;
+                            Some(n as usize)
+                        },
+                        _ => None,
+                    };
+                    let points = |n: usize| (times * (runs * n + 2 * pairs)) as i32;
+                    let n = infer_size(said, 3 ..= 5, points, gained(&count, j));
+                    if !(3 ..= 5).contains(&n) {
+                        return Err(format!("there's no such thing as a run of {}", n));
+                    }
+                    count.claim(Component::Runs(n), times * runs);
+                    if pairs > 0 {
+                        count.claim(Component::Pairs, times * pairs);
+                    }
+                    i = j;
+                },
+                "flush" => {
+                    let mut j = i + 1;
+                    let said = match (word(j), number(j + 1)) {
+                        (Some("of"), Some(n)) => {
+                            j += 2;
+                            Some(n as usize)
+                        },
+                        _ => None,
+                    };
+                    let n = infer_size(said, 4 ..= 5, |n| n as i32, gained(&count, j));
+                    if !(4 ..= 5).contains(&n) {
+                        return Err(format!("there's no such thing as a flush of {}", n));
+                    }
+                    count.claim(Component::Flush(n), 1);
+                    i = j;
+                },
+                "nobs" | "nob" | "knobs" | "knob" => {
+                    count.claim(Component::Nobs, 1);
+                    i += 1;
+                },
+                "for" => i += 1,
+                "of" => return Err("of what?".to_owned()),
+                other => return Err(format!("I don't know what {:?} means", other)),
+            },
+        }
+    }
+
+    if count.claims.is_empty() && count.totals.iter().any(|&(said, _)| said != 0) {
+        return Err("say what the points are for, like \"fifteen two and a pair is four\"".to_owned());
+    }
+    Ok(count)
+}
+
+#[test]
+fn test_parse() {
+    let count = parse("Fifteen two, fifteen four, and a pair is six.").unwrap();
+    assert_eq!(count.claims[&Component::Fifteens], 2);
+    assert_eq!(count.claims[&Component::Pairs], 1);
+    assert_eq!(count.totals, vec![(2, 2), (4, 4), (6, 6)]);
+
+    let count = parse("fifteen 2, 4, 6 and a double run is 14, and one for his nobs is 15").unwrap();
+    assert_eq!(count.claims[&Component::Fifteens], 3);
+    assert_eq!(count.claims[&Component::Runs(3)], 2);
+    assert_eq!(count.claims[&Component::Pairs], 1);
+    assert_eq!(count.claims[&Component::Nobs], 1);
+    assert_eq!(count.total(), 15);
+    assert!(count.totals.iter().all(|&(said, actual)| said == actual));
+
+    // The size of the run comes from the total after it, and "fifteen six" is three fifteens.
+    let count = parse("fifteen six, run of four is ten, flush fifteen").unwrap();
+    assert_eq!(count.claims[&Component::Fifteens], 3);
+    assert_eq!(count.claims[&Component::Runs(4)], 1);
+    assert_eq!(count.claims[&Component::Flush(5)], 1);
+
+    let count = parse("pair royal six, two runs of three is twelve twenty-nine").unwrap();
+    assert_eq!(count.claims[&Component::Pairs], 3);
+    assert_eq!(count.claims[&Component::Runs(3)], 2);
+    assert_eq!(count.totals, vec![(6, 6), (12, 12), (29, 12)]);
+
+    let count = parse("fifteen two, four card flush six, fifteen").unwrap();
+    assert_eq!(count.claims[&Component::Flush(4)], 1);
+    assert_eq!(count.totals, vec![(2, 2), (6, 6), (15, 6)]);
+
+    // Typed digits count the same as the words.
+    for text in &["15 2, 15 4 and a pair is 6", "15-2 15-4 pair 6"] {
+        let count = parse(text).unwrap();
+        assert_eq!(count.claims[&Component::Fifteens], 2, "{}", text);
+        assert_eq!(count.claims[&Component::Pairs], 1, "{}", text);
+        assert_eq!(count.totals, vec![(2, 2), (4, 4), (6, 6)], "{}", text);
+    }
+
+    // Points "for" something aren't a running total.
+    let count = parse("fifteen two and four for the flush is six").unwrap();
+    assert_eq!(count.claims[&Component::Fifteens], 1);
+    assert_eq!(count.claims[&Component::Flush(4)], 1);
+    assert_eq!(count.totals, vec![(2, 2), (6, 6)]);
+    let count = parse("two for fifteen, three for a run, and one for his nobs is six").unwrap();
+    assert_eq!(count.claims[&Component::Fifteens], 1);
+    assert_eq!(count.claims[&Component::Runs(3)], 1);
+    assert_eq!(count.claims[&Component::Nobs], 1);
+    assert_eq!(count.totals, vec![(6, 6)]);
+
+    assert!(parse("fifteen two and a banana").is_err());
+    assert!(parse("twelve").is_err());
+    assert_eq!(parse("nineteen").unwrap_err(), parse("twelve").unwrap_err());
+    assert!(parse("zero").unwrap().claims.is_empty());
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(Component::Pairs.describe(1), "1 pair");
+    assert_eq!(Component::Runs(3).describe(2), "2 runs of 3");
+    assert_eq!(Component::Flush(4).describe(0), "0 4-flushes");
+
+    use crate::card::parse_cards;
+    use crate::hand::Hand;
+
+    let cards = parse_cards("5h 5d kh 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    // Really fifteen four, a double run of three is twelve.
+    let results = check(&parse("fifteen two, pair is four, run of three seven, flush eleven").unwrap(),
+        &hand.find_all_combos());
+    assert_eq!(results, vec![
+        ComponentResult { component: Component::Fifteens, claimed: 1, actual: 2 },
+        ComponentResult { component: Component::Pairs, claimed: 1, actual: 1 },
+        ComponentResult { component: Component::Runs(3), claimed: 1, actual: 2 },
+        ComponentResult { component: Component::Flush(4), claimed: 1, actual: 0 },
+    ]);
+    // Only the one fifteen and the one run that weren't claimed are missed.
+    let combos = hand.find_all_combos();
+    assert_eq!(shortfall(&results[0], &combos).len(), 1);
+    assert!(shortfall(&results[1], &combos).is_empty());
+    assert_eq!(shortfall(&results[2], &combos).len(), 1);
+
+    // One pair out of a pair royal is two pairs short.
+    let cards = parse_cards("4h 4d 4s kc qd");
+    let hand = Hand::with_starter(&cards);
+    let combos = hand.find_all_combos();
+    let results = check(&parse("a pair is two").unwrap(), &combos);
+    let missed = shortfall(&results[0], &combos);
+    assert_eq!(missed.len(), 2);
+    assert!(missed.iter().all(|combo| combo.score == 2));
+}
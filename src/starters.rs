@@ -0,0 +1,180 @@
+// Starters :: what each possible starter card would do for a hand.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// Any card that isn't known to be somewhere else could be the starter: with only the cards kept
+// known, that's 48 cards; counting the ones thrown into the crib too, 46.
+//
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::Hand;
+
+/// What the hand scores with one particular starter.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StarterScore {
+    pub starter: Card,
+    pub score: i32,
+}
+
+/// Scores the held cards with every card left in the deck as the starter. Cards in `elsewhere`,
+/// like the ones thrown into the crib, can't be the starter either.
+pub fn starter_scores(held: &[Card], elsewhere: &[Card], crib: bool) -> Vec<StarterScore> {
+    let mut cards = held.to_vec();
+    Deck::new().cards().iter()
+        .filter(|card| !held.contains(card) && !elsewhere.contains(card))
+        .map(|starter| {
+            cards.push(*starter);
+            let hand = if crib { Hand::crib(&cards) } else { Hand::with_starter(&cards) };
+            let score = hand.score();
+            cards.pop();
+            StarterScore { starter: *starter, score }
+        })
+        .collect()
+}
+
+/// The scores for the starters of one number. Suit only matters for flushes and nobs, so the best
+/// and worst are usually the same.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NumberScore {
+    pub number: i8,
+    /// How many cards of the number are left.
+    pub count: usize,
+    pub best: i32,
+    pub worst: i32,
+}
+
+/// Groups starter scores by number, lowest number first. Numbers with no cards left are left out.
+pub fn by_number(scores: &[StarterScore]) -> Vec<NumberScore> {
+    (1 ..= 13)
+        .filter_map(|number| {
+            let matching: Vec<i32> = scores.iter()
+                .filter(|x| x.starter.number == number)
+                .map(|x| x.score)
+                .collect();
+            Some(NumberScore {
+                number,
+                count: matching.len(),
+                best: *matching.iter().max()?,
+                worst: *matching.iter().min()?,
+            })
+        })
+        .collect()
+}
+
+/// The expected score over all the starters, each as likely as any other, and its variance.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Summary {
+    pub mean: f64,
+    pub variance: f64,
+}
+
+pub fn summarize(scores: &[StarterScore]) -> Summary {
+    let n = scores.len() as f64;
+    let mean = scores.iter().map(|x| f64::from(x.score)).sum::<f64>() / n;
+    let variance = scores.iter().map(|x| (f64::from(x.score) - mean).powi(2)).sum::<f64>() / n;
+    Summary { mean, variance }
+}
+
+/// The chance that the starter gives the hand at least this many points.
+pub fn chance_at_least(scores: &[StarterScore], points: i32) -> f64 {
+    scores.iter().filter(|x| x.score >= points).count() as f64 / scores.len() as f64
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StarterQuestion {
+    /// Which starter number would score the most?
+    Best,
+    /// Which starter numbers could give at least this many points?
+    AtLeast(i32),
+}
+
+impl StarterQuestion {
+    /// The numbers that answer the question. For `Best`, naming any one of them is right; for
+    /// `AtLeast`, the player has to name them all.
+    pub fn answer(&self, numbers: &[NumberScore]) -> Vec<i8> {
+        let threshold = match *self {
+            StarterQuestion::Best => numbers.iter().map(|x| x.best).max().unwrap_or(0),
+            StarterQuestion::AtLeast(points) => points,
+        };
+        numbers.iter().filter(|x| x.best >= threshold).map(|x| x.number).collect()
+    }
+
+    pub fn is_right(&self, numbers: &[NumberScore], chosen: &[i8]) -> bool {
+        let answer = self.answer(numbers);
+        match self {
+            StarterQuestion::Best => chosen.len() == 1 && answer.contains(&chosen[0]),
+            StarterQuestion::AtLeast(_) => {
+                let mut chosen = chosen.to_vec();
+                chosen.sort();
+                chosen.dedup();
+                chosen == answer
+            },
+        }
+    }
+}
+
+/// Asks one or the other kind of question. The threshold is picked so that some numbers reach it
+/// and some don't; if every number scores the same, it asks for the best.
+pub fn ask<R: Rng>(numbers: &[NumberScore], rng: &mut R) -> StarterQuestion {
+    let mut thresholds: Vec<i32> = numbers.iter().map(|x| x.best).collect();
+    thresholds.sort();
+    thresholds.dedup();
+    if thresholds.len() < 2 || rng.gen_bool(0.5) {
+        return StarterQuestion::Best;
+    }
+    StarterQuestion::AtLeast(*thresholds[1 ..].choose(rng).unwrap())
+}
+
+#[test]
+fn test_starters() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::card::parse_cards;
+
+    let held = parse_cards("5h 5d 4s 6c");
+    let scores = starter_scores(&held, &[], false);
+    assert_eq!(scores.len(), 48);
+    let numbers = by_number(&scores);
+    assert_eq!(numbers.len(), 13);
+    assert_eq!(numbers.iter().find(|x| x.number == 5).unwrap().count, 2);
+
+    // Another 4 or 6 makes a double double run; a 5 makes three of a kind in a double run.
+    let five = numbers.iter().find(|x| x.number == 5).unwrap();
+    assert_eq!((five.best, five.worst), (23, 23));
+    let six = numbers.iter().find(|x| x.number == 6).unwrap();
+    assert_eq!((six.best, six.worst), (24, 24));
+    assert_eq!(StarterQuestion::Best.answer(&numbers), vec![4, 6]);
+    assert!(StarterQuestion::Best.is_right(&numbers, &[6]));
+    assert!(!StarterQuestion::Best.is_right(&numbers, &[4, 6]));
+    assert!(StarterQuestion::AtLeast(23).is_right(&numbers, &[6, 5, 4]));
+    assert!(!StarterQuestion::AtLeast(23).is_right(&numbers, &[6, 4]));
+
+    // Nobs only counts with the jack's suit.
+    let held = parse_cards("jh 2c 7s 9d");
+    let discards = parse_cards("qs kh");
+    let scores = starter_scores(&held, &discards, false);
+    assert_eq!(scores.len(), 46);
+    let numbers = by_number(&scores);
+    let queen = numbers.iter().find(|x| x.number == 12).unwrap();
+    assert_eq!(queen.best, queen.worst + 1);
+
+    // Without the right starter, this hand has nothing at all.
+    let summary = summarize(&scores);
+    let total: i32 = scores.iter().map(|x| x.score).sum();
+    assert!((summary.mean - f64::from(total) / 46.).abs() < 1e-9);
+    assert!(summary.variance > 0.);
+    assert_eq!(chance_at_least(&scores, 0), 1.);
+    assert!(chance_at_least(&scores, 2) < 1.);
+
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0 .. 20 {
+        let question = ask(&numbers, &mut rng);
+        let answer = question.answer(&numbers);
+        assert!(!answer.is_empty() && answer.len() < numbers.len());
+        assert!(question.is_right(&numbers, &answer[.. if question == StarterQuestion::Best { 1 } else { answer.len() }]));
+    }
+}
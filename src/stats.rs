@@ -0,0 +1,167 @@
+// Stats :: how often the player misses each category of combo, kept from one session to the next.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::combo::{Category, Combo};
+use crate::hand::Hand;
+
+/// The most deals to choose a hand from, once the player is getting nearly everything right.
+const MAX_ADAPTIVE_DEALS: f64 = 16.;
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct CategoryStats {
+    /// Points in combos of the category that came up in the player's hands.
+    pub seen: u32,
+    /// How many of those points the player missed.
+    pub missed: u32,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Stats {
+    pub categories: BTreeMap<Category, CategoryStats>,
+}
+
+impl Stats {
+    /// Counts the points in every combo in a hand, and in the ones the player missed. It's points
+    /// rather than combos, so that it doesn't matter how they're grouped: a pair royal missed after
+    /// claiming one of its pairs is two pairs missed, out of the same six points.
+    pub fn record(&mut self, combos: &[Combo<'_>], missed: &[Combo<'_>]) {
+        for combo in combos {
+            self.categories.entry(combo.category()).or_default().seen += combo.score as u32;
+        }
+        for combo in missed {
+            self.record_missed(combo.category(), combo.score as u32);
+        }
+    }
+
+    /// Counts points missed in a category, for a total that doesn't say which combos they were in.
+    pub fn record_missed(&mut self, category: Category, points: u32) {
+        self.categories.entry(category).or_default().missed += points;
+    }
+
+    /// The fraction of points of the category that get missed. Until there's much to go on, it
+    /// stays close to one in two.
+    pub fn miss_rate(&self, category: Category) -> f64 {
+        let stats = self.categories.get(&category).cloned().unwrap_or_default();
+        f64::from(stats.missed.min(stats.seen) + 1) / f64::from(stats.seen + 2)
+    }
+
+    /// The fraction of all points the player has counted, over every category.
+    pub fn accuracy(&self) -> f64 {
+        let (seen, missed) = self.categories.values()
+            .fold((0u32, 0u32), |(seen, missed), stats| (seen + stats.seen, missed + stats.missed));
+        f64::from(seen.saturating_sub(missed) + 1) / f64::from(seen + 2)
+    }
+
+    /// The category missed most often.
+    pub fn weakest(&self) -> Option<Category> {
+        self.categories.keys()
+            .max_by(|a, b| self.miss_rate(**a).partial_cmp(&self.miss_rate(**b)).unwrap())
+            .cloned()
+    }
+
+    /// How many points the hand has in the categories the player tends to miss: each combo's score,
+    /// weighted by how often its category gets missed.
+    pub fn weakness(&self, hand: &Hand<'_>) -> f64 {
+        hand.find_all_combos().iter()
+            .map(|combo| f64::from(combo.score) * self.miss_rate(combo.category()))
+            .sum()
+    }
+
+    /// How many deals to pick the hand from. A player who misses a lot gets hands close to random,
+    /// and as they get better, the hands get picked harder for what they miss.
+    pub fn adaptive_deals(&self) -> usize {
+        1 + (self.accuracy() * self.accuracy() * MAX_ADAPTIVE_DEALS) as usize
+    }
+
+    pub fn load(path: &Path) -> io::Result<Stats> {
+        let mut stats = Stats::default();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match fields.as_slice() {
+                [name, seen, missed] => Category::from_name(name)
+                    .and_then(|category| Some((category, seen.parse().ok()?, missed.parse().ok()?))),
+                _ => None,
+            };
+            match parsed {
+                Some((category, seen, missed)) => {
+                    stats.categories.insert(category, CategoryStats { seen, missed });
+                },
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, "bad stats line")),
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(path)?;
+        for (category, stats) in &self.categories {
+            writeln!(file, "{} {} {}", category, stats.seen, stats.missed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the player's records are kept: `$XDG_DATA_HOME/cribbagepractice`, or
+/// `~/.local/share/cribbagepractice`.
+pub fn data_dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(dir.join("cribbagepractice"))
+}
+
+pub fn stats_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("stats.txt"))
+}
+
+#[test]
+fn test_stats() {
+    use crate::card::parse_cards;
+    use crate::combo::ComboKind;
+
+    let cards = parse_cards("5h kh 5d 3s 4s");
+    let hand = Hand::with_starter(&cards);
+    let combos = hand.find_all_combos();
+    let missed: Vec<Combo<'_>> = combos.iter().filter(|x| x.kind == ComboKind::Fifteen).cloned().collect();
+
+    let mut stats = Stats::default();
+    let beginner_deals = stats.adaptive_deals();
+    for _ in 0 .. 10 {
+        stats.record(&combos, &missed);
+    }
+    assert_eq!(stats.categories[&Category::Fifteens], CategoryStats { seen: 40, missed: 40 });
+    assert_eq!(stats.weakest(), Some(Category::Fifteens));
+    assert!(stats.miss_rate(Category::Runs) < 0.1);
+    // The pair and the runs are all counted, so fifteens are weighted most.
+    assert!(stats.weakness(&hand) > 4. * stats.miss_rate(Category::Fifteens) - 0.01);
+
+    // Getting everything right makes for harder picking.
+    for _ in 0 .. 40 {
+        stats.record(&combos, &[]);
+    }
+    assert!(stats.adaptive_deals() > beginner_deals);
+
+    let path = env::temp_dir().join(format!("cribbagepractice-stats-{}.txt", std::process::id()));
+    stats.save(&path).unwrap();
+    let loaded = Stats::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, stats);
+
+    // A file edited by hand could say more was missed than seen.
+    let mut stats = Stats::default();
+    stats.categories.insert(Category::Pairs, CategoryStats { seen: 2, missed: 6 });
+    assert_eq!(stats.accuracy(), 0.25);
+    assert_eq!(stats.miss_rate(Category::Pairs), 0.75);
+}
@@ -0,0 +1,222 @@
+// Table :: a precomputed score for every show, for tools that score millions of hands.
+//
+// Copyright (c) 2016 by William R. Fraser
+//
+// The table has a byte for every four held cards and every starter, about 14 million of them, so a
+// lookup is just an index into it. It takes a few seconds to build, so it's cached on disk after the
+// first time.
+//
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::packed::{self, CardSet};
+
+/// Starts every cache file, followed by `VERSION`. Bump the version whenever the layout or the
+/// scoring changes, so that an old cache gets rebuilt instead of trusted.
+const MAGIC: &[u8; 8] = b"CRIBTBL\0";
+const VERSION: u32 = 2;
+
+/// How many ways there are to hold four cards out of the deck.
+const HELD: usize = 270_725;
+
+/// Marks a show the table was built without.
+const MISSING: u8 = u8::MAX;
+
+static TABLE: OnceLock<ScoreTable> = OnceLock::new();
+
+pub struct ScoreTable {
+    scores: Vec<u8>,
+}
+
+impl ScoreTable {
+    /// Scores every show that can be made from a full deck.
+    pub fn build() -> ScoreTable {
+        ScoreTable::build_from(Deck::new().cards())
+    }
+
+    /// Scores every show that can be made from the given cards.
+    pub fn build_from(cards: &[Card]) -> ScoreTable {
+        let mut scores = vec![MISSING; HELD * 52];
+        let n = cards.len();
+        for a in 0 .. n {
+            for b in a + 1 .. n {
+                for c in b + 1 .. n {
+                    for d in c + 1 .. n {
+                        let held = CardSet::from_cards(&[cards[a], cards[b], cards[c], cards[d]]);
+                        for starter in cards {
+                            if !held.contains(starter) {
+                                scores[index(held, starter)] = packed::score(held, starter);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ScoreTable { scores }
+    }
+
+    /// How many shows are in the table.
+    pub fn len(&self) -> usize {
+        self.scores.iter().filter(|&&score| score != MISSING).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up the score of four held cards plus the starter, or `None` if the table was built
+    /// without them.
+    pub fn score(&self, held: CardSet, starter: &Card) -> Option<u8> {
+        if held.len() != 4 || held.contains(starter) {
+            return None;
+        }
+        Some(self.scores[index(held, starter)]).filter(|&score| score != MISSING)
+    }
+
+    pub fn load(path: &Path) -> io::Result<ScoreTable> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut file = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a score table"));
+        }
+
+        let mut version = [0u8; 4];
+        file.read_exact(&mut version)?;
+        if u32::from_le_bytes(version) != VERSION {
+            return Err(invalid("score table is from another version"));
+        }
+
+        let mut scores = vec![0u8; HELD * 52];
+        file.read_exact(&mut scores)?;
+        if file.read(&mut [0u8])? != 0 {
+            return Err(invalid("score table is too long"));
+        }
+        Ok(ScoreTable { scores })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&self.scores)?;
+        file.flush()
+    }
+
+    /// Loads the table from the cache, or builds it and saves it there if it isn't cached yet, or
+    /// the cached one isn't usable. A table that couldn't be saved is still returned, along with
+    /// the reason.
+    pub fn load_or_build() -> (ScoreTable, io::Result<()>) {
+        let path = cache_path();
+        if let Some(ref path) = path {
+            if let Ok(table) = ScoreTable::load(path) {
+                return (table, Ok(()));
+            }
+        }
+
+        let table = ScoreTable::build();
+        let saved = match path {
+            Some(ref path) => table.save(path),
+            None => Ok(()),
+        };
+        (table, saved)
+    }
+}
+
+/// Where a show goes in the table: the held cards' place among every four cards in deck order,
+/// then the starter.
+fn index(held: CardSet, starter: &Card) -> usize {
+    let mut rank = 0;
+    let mut bits = held.0;
+    for k in 1 ..= 4 {
+        let position = bits.trailing_zeros() as usize;
+        rank += choose(position, k);
+        bits &= bits - 1;
+    }
+    rank * 52 + starter.index()
+}
+
+fn choose(n: usize, k: usize) -> usize {
+    if n < k {
+        return 0;
+    }
+    (0 .. k).fold(1, |total, i| total * (n - i) / (i + 1))
+}
+
+/// Where the table is cached: `$XDG_CACHE_HOME/cribbagepractice`, or `~/.cache/cribbagepractice`.
+pub fn cache_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(dir.join("cribbagepractice").join("scores.bin"))
+}
+
+/// Loads (or builds) the table and makes `Hand::score` use it from then on. It's used even if
+/// it couldn't be saved to the cache; that only comes back from the call that built it.
+pub fn enable() -> (&'static ScoreTable, io::Result<()>) {
+    let mut saved = Ok(());
+    let table = TABLE.get_or_init(|| {
+        let (table, result) = ScoreTable::load_or_build();
+        saved = result;
+        table
+    });
+    (table, saved)
+}
+
+/// The table, if `enable` has been called.
+pub fn get() -> Option<&'static ScoreTable> {
+    TABLE.get()
+}
+
+#[test]
+fn test_score_table() {
+    use crate::hand::Hand;
+
+    // The first 16 cards of the deck are aces through fours, which make plenty of runs, pairs,
+    // flushes, and fifteens.
+    let deck = Deck::new();
+    let cards = &deck.cards()[.. 16];
+    let table = ScoreTable::build_from(cards);
+    assert_eq!(table.len(), 1820 * 12);
+    assert_eq!(table.score(CardSet::from_cards(&deck.cards()[48 .. 52]), &cards[0]), None);
+
+    let mut show = [cards[0]; 5];
+    for (i, card) in show.iter_mut().enumerate() {
+        *card = cards[i * 3 + 1];
+    }
+    let held = CardSet::from_cards(&show[.. 4]);
+    assert_eq!(table.score(held, &show[4]).map(i32::from), Some(Hand::with_starter(&show).score()));
+
+    let path = env::temp_dir().join(format!("cribbagepractice-test-{}.bin", std::process::id()));
+    table.save(&path).unwrap();
+    let loaded = ScoreTable::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.len(), table.len());
+    assert_eq!(loaded.score(held, &show[4]), table.score(held, &show[4]));
+
+    // A cache from another version, or a truncated one, isn't trusted.
+    fs::write(&path, b"CRIBTBL\0\x01\0\0\0").unwrap();
+    assert!(ScoreTable::load(&path).is_err());
+    let mut short = MAGIC.to_vec();
+    short.extend_from_slice(&VERSION.to_le_bytes());
+    short.extend_from_slice(&[0; 100]);
+    fs::write(&path, short).unwrap();
+    assert!(ScoreTable::load(&path).is_err());
+    fs::remove_file(&path).unwrap();
+
+    // Every four cards get a place of their own.
+    assert_eq!(choose(52, 4), HELD);
+    let last = CardSet::from_cards(&deck.cards()[48 .. 52]);
+    assert_eq!(index(last, &cards[0]), (HELD - 1) * 52);
+}
@@ -4,23 +4,99 @@
 //
 
 use crate::card::Card;
-use crate::combo::Combo;
+use crate::catch::Verdict;
+use crate::choice::Question;
+use crate::combo::{Category, Combo, ComboKind};
+use crate::cross::{CrossGrid, Line};
 use crate::hand::Hand;
+use crate::lessons::Unit;
+use crate::spoken::{ComponentResult, SpokenCount};
+use crate::starters::{NumberScore, StarterQuestion};
 
+#[derive(Debug, Clone)]
 pub struct Guess {
     pub cards: Vec<Card>,
     pub score: i8,
 }
 
+/// What the player does next while counting their hand.
+pub enum Action {
+    Guess(Guess),
+    /// Ask for a hint about what's left to count.
+    Hint,
+    /// Take back the last guess.
+    Undo,
+    /// Done counting; whatever's left over goes to the computer.
+    Done,
+}
+
+/// Help for a stuck player. Each one given for a hand reveals more than the one before.
+pub enum Hint {
+    /// How many combos are left to count.
+    Count(usize),
+    /// How many combos of each category are left.
+    Categories(Vec<(Category, usize)>),
+    /// A card that's part of a combo that's left, and what kind of combo.
+    Card(Card, ComboKind),
+}
+
+/// A player's announced total for one category of combos, e.g. "fifteens 6".
+pub struct CategoryClaim {
+    pub category: Category,
+    pub score: i8,
+}
+
 pub trait UserInterface {
     fn display_hand(&mut self, hand: &Hand<'_>);
-    fn display_missed_combos(&mut self, combos: &[Combo<'_>]);
+    fn display_missed_combos(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]);
     fn display_win_message(&mut self, score: i32);
     fn display_lose_message(&mut self, score: i32);
     fn display_bad_guess_wrong_score(&mut self, actual_combo: &Combo<'_>);
-    fn display_bad_guess_invalid_combo(&mut self);
+    fn display_bad_guess_invalid_combo(&mut self, reasons: &[String]);
     fn display_correct_guess(&mut self, combo: &Combo<'_>);
+    /// The guess is a real combo, but not of the category being drilled, so it doesn't count.
+    fn display_outside_drill(&mut self, category: Category, drill: Category);
     fn add_score_player(&mut self, score: i32);
     fn add_score_cpu(&mut self, score: i32);
-    fn get_guess(&mut self, hand: &Hand<'_>) -> Option<Guess>;
+    fn display_category_result(&mut self, category: Category, claimed: i8, actual: i8);
+    fn display_hint(&mut self, hint: &Hint, cost: i32);
+    fn display_undo(&mut self, guess: Option<&Guess>);
+    fn display_pone_bonus(&mut self, points: i32);
+    /// Shows the computer's hand, which it counts itself.
+    fn display_cpu_hand(&mut self, hand: &Hand<'_>, score: i32);
+    /// Shows a cross-cribbage grid, marking the line to count next, or `None` for the crib.
+    fn display_cross(&mut self, grid: &CrossGrid, line: Option<Line>);
+    /// Shows the computer's hand and the combos it claims.
+    fn display_cpu_announcement(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]);
+    /// Asks which of the computer's combos are wrong or missing, as a set of cards for each. None
+    /// means the player accepts the count.
+    fn get_muggins_calls(&mut self, hand: &Hand<'_>) -> Vec<Vec<Card>>;
+    fn display_verdict(&mut self, hand: &Hand<'_>, verdict: &Verdict<'_>);
+    /// Asks a multiple-choice question, returning the index of the choice picked.
+    fn get_choice(&mut self, hand: &Hand<'_>, question: &Question<'_>) -> usize;
+    fn display_choice_result(&mut self, hand: &Hand<'_>, question: &Question<'_>, chosen: usize);
+    /// Shows what a lesson unit is about, before its exercises.
+    fn display_lesson(&mut self, unit: &Unit);
+    fn display_lesson_result(&mut self, unit: &Unit, perfect: usize);
+    /// Asks a question about which starters would help the cards kept, returning the numbers
+    /// picked.
+    fn get_starter_answer(&mut self, held: &[Card], question: &StarterQuestion) -> Vec<i8>;
+    fn display_starter_result(&mut self, held: &[Card], question: &StarterQuestion, numbers: &[NumberScore],
+        right: bool);
+    fn get_action(&mut self, hand: &Hand<'_>) -> Action;
+    /// Once the player is done, asks for the total they're pegging, or `None` if they'd rather not
+    /// say and just take what they counted.
+    fn get_declared_total(&mut self, hand: &Hand<'_>) -> Option<i32>;
+    /// The total declared isn't what the combos the player counted add up to.
+    fn display_declared_total(&mut self, declared: i32, counted: i32);
+    /// The computer calls muggins on a combo the player missed.
+    fn display_muggins(&mut self, hand: &Hand<'_>, combo: &Combo<'_>);
+    /// Combos the player missed that the computer didn't notice either.
+    fn display_unnoticed(&mut self, hand: &Hand<'_>, combos: &[Combo<'_>]);
+    fn get_category_claims(&mut self, hand: &Hand<'_>) -> Vec<CategoryClaim>;
+    /// Asks for the count the way it's said out loud.
+    fn get_spoken_count(&mut self, hand: &Hand<'_>) -> SpokenCount;
+    fn display_component_result(&mut self, result: &ComponentResult);
+    /// A running total the player said that isn't what their claims added up to.
+    fn display_wrong_total(&mut self, said: i32, actual: i32);
 }
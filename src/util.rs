@@ -68,6 +68,7 @@ pub fn group_by<'a, T, K, F>(items: &'a [T], f: F) -> BTreeMap<K, Vec<&'a T>>
 }
 
 #[test]
+#[allow(clippy::unnecessary_get_then_check)]
 fn test_group_by() {
     let items = vec![1, 2, 3, 10, 11, 20];
     let by_tens = group_by(&items, |n| n / 10);